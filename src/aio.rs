@@ -0,0 +1,136 @@
+use redis::aio::ConnectionLike;
+use redis::{RedisFuture, ToRedisArgs, Value};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::encode::to_hash_fields;
+use crate::from_redis_value;
+
+/// Typed commands for any `redis::aio::ConnectionLike`
+///
+/// The async counterpart of `RedisSerdeExt`, covering `MultiplexedConnection`,
+/// `ConnectionManager` and cluster connections alike. Replies are decoded with
+/// `from_redis_value` as soon as they arrive.
+///
+/// ```no_run
+/// # #[macro_use] extern crate serde_derive;
+/// use serde_redis::AsyncRedisSerdeExt;
+///
+/// #[derive(Deserialize)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// # async fn f(mut con: redis::aio::MultiplexedConnection) -> redis::RedisResult<()> {
+/// let user: User = con.hget_struct("user:1").await?;
+/// # Ok(())
+/// # }
+/// # fn main() {}
+/// ```
+pub trait AsyncRedisSerdeExt: ConnectionLike + Send + Sized {
+    /// `HGETALL key`, decoded into `T`
+    fn hget_struct<'a, K, T>(&'a mut self, key: K) -> RedisFuture<'a, T>
+    where
+        K: ToRedisArgs,
+        T: DeserializeOwned + Send + 'a,
+    {
+        let mut cmd = redis::cmd("HGETALL");
+        cmd.arg(key);
+        query_as(self, cmd)
+    }
+
+    /// `HSET key field value...` with the fields of `value`
+    ///
+    /// Fields that are `None` are left out; they are not removed from an existing hash.
+    fn hset_struct<'a, K, T>(&'a mut self, key: K, value: &T) -> RedisFuture<'a, ()>
+    where
+        K: ToRedisArgs,
+        T: Serialize + ?Sized,
+    {
+        let mut cmd = redis::cmd("HSET");
+        cmd.arg(key);
+
+        let mut empty = true;
+        let fields = to_hash_fields(value);
+        Box::pin(async move {
+            for (field, value) in fields? {
+                if let Value::Data(bytes) = value {
+                    cmd.arg(field).arg(bytes);
+                    empty = false;
+                }
+            }
+
+            if empty {
+                return Ok(());
+            }
+
+            cmd.query_async(self).await
+        })
+    }
+
+    /// `GET key`, decoded into `T`
+    ///
+    /// Use `Option<T>` to handle missing keys.
+    fn get_as<'a, K, T>(&'a mut self, key: K) -> RedisFuture<'a, T>
+    where
+        K: ToRedisArgs,
+        T: DeserializeOwned + Send + 'a,
+    {
+        let mut cmd = redis::cmd("GET");
+        cmd.arg(key);
+        query_as(self, cmd)
+    }
+
+    /// `MGET key...`, decoded into one `T` per key
+    ///
+    /// Use `Option<T>` to handle missing keys.
+    fn mget_as<'a, K, T>(&'a mut self, keys: K) -> RedisFuture<'a, Vec<T>>
+    where
+        K: ToRedisArgs,
+        T: DeserializeOwned + Send + 'a,
+    {
+        let mut cmd = redis::cmd("MGET");
+        cmd.arg(keys);
+        query_as(self, cmd)
+    }
+
+    /// `LRANGE key start stop`, decoded into one `T` per element
+    fn lrange_as<'a, K, T>(
+        &'a mut self,
+        key: K,
+        start: isize,
+        stop: isize,
+    ) -> RedisFuture<'a, Vec<T>>
+    where
+        K: ToRedisArgs,
+        T: DeserializeOwned + Send + 'a,
+    {
+        let mut cmd = redis::cmd("LRANGE");
+        cmd.arg(key).arg(start).arg(stop);
+        query_as(self, cmd)
+    }
+
+    /// `SMEMBERS key`, decoded into one `T` per member
+    fn smembers_as<'a, K, T>(&'a mut self, key: K) -> RedisFuture<'a, Vec<T>>
+    where
+        K: ToRedisArgs,
+        T: DeserializeOwned + Send + 'a,
+    {
+        let mut cmd = redis::cmd("SMEMBERS");
+        cmd.arg(key);
+        query_as(self, cmd)
+    }
+}
+
+impl<C> AsyncRedisSerdeExt for C where C: ConnectionLike + Send {}
+
+fn query_as<'a, C, T>(con: &'a mut C, cmd: redis::Cmd) -> RedisFuture<'a, T>
+where
+    C: ConnectionLike + Send,
+    T: DeserializeOwned + Send + 'a,
+{
+    Box::pin(async move {
+        let value: Value = cmd.query_async(con).await?;
+        Ok(from_redis_value(value)?)
+    })
+}
@@ -0,0 +1,176 @@
+//! Write JSON lines or CSV records as commands for `redis-cli --pipe`
+//!
+//! ```text
+//! serde-redis-mass-insert --key 'user:{id}' users.jsonl | redis-cli --pipe
+//! ```
+use serde::ser::{Serialize, Serializer};
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::process;
+
+use serde_redis::mass_insert::{KeyTemplate, Store, Writer};
+
+const USAGE: &str = "\
+usage: serde-redis-mass-insert --key TEMPLATE [options] [INPUT]
+
+Reads records from INPUT (or stdin) and writes the commands that store them to
+stdout, for `redis-cli --pipe`.
+
+options:
+    --key TEMPLATE     key for each record, such as user:{id}
+    --format FORMAT    json (one object per line) or csv; defaults to csv for
+                       .csv files and json otherwise
+    --set FIELD        SET the key to FIELD instead of HSET of all fields
+    --rpush FIELD      RPUSH FIELD onto the list at the key instead
+    --expire SECONDS   EXPIRE each key after writing it
+    -o, --output FILE  write to FILE instead of stdout
+    -h, --help         print this message";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Json,
+    Csv,
+}
+
+#[derive(Debug)]
+struct Options {
+    key: KeyTemplate,
+    format: Option<Format>,
+    store: Store,
+    expire: Option<u64>,
+    input: Option<String>,
+    output: Option<String>,
+}
+
+fn parse_args<I>(mut args: I) -> Result<Options, Box<dyn Error>>
+where
+    I: Iterator<Item = String>,
+{
+    let mut key: Option<KeyTemplate> = None;
+    let mut format = None;
+    let mut store = Store::Hash;
+    let mut expire = None;
+    let mut input = None;
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("{} needs a value", name));
+
+        match arg.as_str() {
+            "--key" => key = Some(value("--key")?.parse()?),
+            "--format" => {
+                format = Some(match value("--format")?.as_str() {
+                    "json" => Format::Json,
+                    "csv" => Format::Csv,
+                    other => return Err(format!("unknown format {}", other).into()),
+                })
+            }
+            "--set" => store = Store::String(value("--set")?),
+            "--rpush" => store = Store::List(value("--rpush")?),
+            "--expire" => expire = Some(value("--expire")?.parse()?),
+            "-o" | "--output" => output = Some(value("--output")?),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            flag if flag.starts_with('-') && flag != "-" => {
+                return Err(format!("unknown option {}", flag).into())
+            }
+            _ if input.is_some() => return Err("only one INPUT is allowed".into()),
+            _ => input = Some(arg),
+        }
+    }
+
+    Ok(Options {
+        key: key.ok_or("--key is required")?,
+        format,
+        store,
+        expire,
+        input,
+        output,
+    })
+}
+
+/// A CSV row as a map from header to cell, keeping the column order
+struct Row<'a> {
+    headers: &'a csv::StringRecord,
+    record: &'a csv::StringRecord,
+}
+
+impl<'a> Serialize for Row<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_map(self.headers.iter().zip(self.record.iter()))
+    }
+}
+
+fn run(options: Options) -> Result<usize, Box<dyn Error>> {
+    let input: Box<dyn Read> = match options.input.as_deref() {
+        None | Some("-") => Box::new(io::stdin()),
+        Some(path) => Box::new(File::open(path)?),
+    };
+    let output: Box<dyn Write> = match options.output {
+        None => Box::new(io::stdout()),
+        Some(ref path) => Box::new(File::create(path)?),
+    };
+
+    let format = options.format.unwrap_or_else(|| match options.input {
+        Some(ref path) if path.ends_with(".csv") => Format::Csv,
+        _ => Format::Json,
+    });
+
+    let mut writer = Writer::new(BufWriter::new(output), options.key).store(options.store);
+    if let Some(seconds) = options.expire {
+        writer = writer.expire(seconds);
+    }
+
+    match format {
+        Format::Json => {
+            for (n, line) in BufReader::new(input).lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                let record: serde_json::Map<String, serde_json::Value> =
+                    serde_json::from_str(&line)
+                        .map_err(|err| format!("line {}: {}", n + 1, err))?;
+                writer
+                    .write(&record)
+                    .map_err(|err| format!("line {}: {}", n + 1, err))?;
+            }
+        }
+        Format::Csv => {
+            let mut reader = csv::Reader::from_reader(input);
+            let headers = reader.headers()?.clone();
+            for (n, record) in reader.records().enumerate() {
+                let record = record?;
+                writer
+                    .write(&Row {
+                        headers: &headers,
+                        record: &record,
+                    })
+                    .map_err(|err| format!("record {}: {}", n + 1, err))?;
+            }
+        }
+    }
+
+    writer.flush()?;
+    Ok(writer.commands())
+}
+
+fn main() {
+    let result = parse_args(std::env::args().skip(1)).and_then(run);
+
+    match result {
+        Ok(commands) => eprintln!("wrote {} commands", commands),
+        Err(err) => {
+            eprintln!("serde-redis-mass-insert: {}", err);
+            eprintln!("run with --help for usage");
+            process::exit(1);
+        }
+    }
+}
@@ -27,4 +27,11 @@ impl<'a> Iterator for CowIter<'a> {
             CowIter::Owned(iter) => iter.next().map(Cow::Owned),
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            CowIter::Borrowed(iter) => iter.size_hint(),
+            CowIter::Owned(iter) => iter.size_hint(),
+        }
+    }
 }
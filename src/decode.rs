@@ -1,14 +1,16 @@
 use redis::Value;
 use serde::{self, de};
 use std::borrow::Cow;
+use std::convert::TryFrom;
 use std::fmt::{self, Display};
 use std::iter::Peekable;
+use std::marker::PhantomData;
 use std::{error, num, str, string, vec};
 
 use crate::cow_iter::CowIter;
 
 /// Error that can be produced during deserialization
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Error {
     Custom(String),
     EndOfStream,
@@ -18,6 +20,8 @@ pub enum Error {
     DuplicateField(&'static str),
     DeserializeNotSupported,
     WrongValue(String),
+    /// Values were left over after deserializing; holds how many
+    TrailingValues(usize),
     StrFromUtf8(str::Utf8Error),
     StringFromUtf8(string::FromUtf8Error),
     ParseInt(num::ParseIntError),
@@ -64,6 +68,7 @@ impl fmt::Display for Error {
             Error::DuplicateField(ref field) => write!(f, "duplicate field {:?}", field),
             Error::DeserializeNotSupported => write!(f, "Deserialization option not supported"),
             Error::WrongValue(ref value_type) => write!(f, "Got unexpected value: {}", value_type),
+            Error::TrailingValues(count) => write!(f, "{} trailing values", count),
             Error::StrFromUtf8(ref e) => write!(f, "{}", e),
             Error::StringFromUtf8(ref e) => write!(f, "{}", e),
             Error::ParseInt(ref e) => write!(f, "{}", e),
@@ -96,6 +101,16 @@ impl de::Error for Error {
     }
 }
 
+impl From<Error> for redis::RedisError {
+    fn from(err: Error) -> redis::RedisError {
+        redis::RedisError::from((
+            redis::ErrorKind::TypeError,
+            "Response could not be deserialized",
+            err.to_string(),
+        ))
+    }
+}
+
 impl From<str::Utf8Error> for Error {
     fn from(err: str::Utf8Error) -> Error {
         Error::StrFromUtf8(err)
@@ -132,9 +147,11 @@ impl From<num::ParseFloatError> for Error {
 #[derive(Debug)]
 pub struct Deserializer<'a> {
     values: Peekable<vec::IntoIter<Cow<'a, Value>>>,
+    lua: bool,
 }
 
 pub trait AsValueVec<'a> {
+    #[allow(clippy::wrong_self_convention)]
     fn as_value_vec(self) -> Vec<Cow<'a, Value>>;
 }
 
@@ -173,6 +190,56 @@ impl<'a> Deserializer<'a> {
     {
         Deserializer {
             values: values.as_value_vec().into_iter().peekable(),
+            lua: false,
+        }
+    }
+
+    /// Create a Deserializer for the result of a Lua script
+    ///
+    /// Redis converts Lua values on the way out: `true` becomes `Int(1)`, `false` becomes `Nil`,
+    /// and tables stop at their first `nil`. In this mode `bool` also decodes from `Int(1)`,
+    /// `Int(0)` and `Nil`, and tuples missing trailing elements are padded with `Nil`, so their
+    /// `Option` elements decode as `None`. Nested values are decoded the same way.
+    ///
+    /// Note that `Option<bool>` decodes a Lua `false` as `None`.
+    pub fn lua<V>(values: V) -> Self
+    where
+        V: AsValueVec<'a>,
+    {
+        Deserializer {
+            lua: true,
+            ..Deserializer::new(values)
+        }
+    }
+
+    /// Check that every value was deserialized
+    ///
+    /// Deserializing through `&mut Deserializer` reads one value and leaves the rest; call this
+    /// afterwards when exactly one value is expected.
+    pub fn end(&mut self) -> Result<()> {
+        match self.values.len() {
+            0 => Ok(()),
+            count => Err(Error::TrailingValues(count)),
+        }
+    }
+
+    /// Turn into an iterator deserializing one `T` per remaining value
+    #[allow(clippy::should_implement_trait)]
+    pub fn into_iter<T>(self) -> StreamDeserializer<'a, T>
+    where
+        T: de::DeserializeOwned,
+    {
+        StreamDeserializer {
+            de: self,
+            failed: false,
+            output: PhantomData,
+        }
+    }
+
+    fn nested(value: Cow<'a, Value>, lua: bool) -> Self {
+        Deserializer {
+            lua,
+            ..Deserializer::new(value)
         }
     }
 
@@ -186,6 +253,7 @@ impl<'a> Deserializer<'a> {
 
     /// Return the next value
     #[inline]
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> Result<Cow<'a, Value>> {
         match self.values.next() {
             Some(value) => Ok(value),
@@ -197,7 +265,7 @@ impl<'a> Deserializer<'a> {
         match self.next()? {
             Cow::Owned(Value::Bulk(values)) => Ok(Cow::Owned(values)),
             Cow::Borrowed(Value::Bulk(values)) => Ok(Cow::Borrowed(values)),
-            v @ _ => Err(Error::wrong_value(format!("expected bulk but got {:?}", v))),
+            v => Err(Error::wrong_value(format!("expected bulk but got {:?}", v))),
         }
     }
 
@@ -207,7 +275,7 @@ impl<'a> Deserializer<'a> {
             Cow::Borrowed(Value::Data(bytes)) => Ok(Cow::Borrowed(bytes)),
             v => {
                 let msg = format!("Expected bytes, but got {:?}", v);
-                return Err(Error::wrong_value(msg));
+                Err(Error::wrong_value(msg))
             }
         }
     }
@@ -217,18 +285,38 @@ impl<'a> Deserializer<'a> {
         Ok(match redis_value {
             Cow::Owned(Value::Data(bytes)) => Cow::Owned(String::from_utf8(bytes)?),
             Cow::Borrowed(Value::Data(bytes)) => Cow::Borrowed(str::from_utf8(bytes)?),
+            Cow::Owned(Value::Status(s)) => Cow::Owned(s),
+            Cow::Borrowed(Value::Status(s)) => Cow::Borrowed(s.as_str()),
+            Cow::Owned(Value::Okay) | Cow::Borrowed(Value::Okay) => Cow::Borrowed("OK"),
             _ => {
-                let msg = format!("Expected Data, got {:?}", &redis_value);
+                let msg = format!("Expected Data, Status or Okay, got {:?}", &redis_value);
                 return Err(Error::wrong_value(msg));
             }
         })
     }
 }
 
+/// Convert an `Int` into a narrower integer type, rejecting values outside its range
+pub(crate) fn int_in_range<T>(i: i64, ty: &str) -> Result<T>
+where
+    T: TryFrom<i64>,
+{
+    T::try_from(i).map_err(|_| Error::wrong_value(format!("{} is out of range for {}", i, ty)))
+}
+
 macro_rules! impl_num {
+    (float $ty:ty, $deserialize_method:ident, $visitor_method:ident) => {
+        impl_num!($ty, $deserialize_method, $visitor_method, |i| i as $ty);
+    };
     ($ty:ty, $deserialize_method:ident, $visitor_method:ident) => {
+        impl_num!($ty, $deserialize_method, $visitor_method, |i| int_in_range(
+            i,
+            stringify!($ty)
+        )?);
+    };
+    ($ty:ty, $deserialize_method:ident, $visitor_method:ident, |$i:ident| $from_int:expr) => {
         #[inline]
-        fn $deserialize_method<V>(mut self, visitor: V) -> Result<V::Value>
+        fn $deserialize_method<V>(self, visitor: V) -> Result<V::Value>
         where
             V: de::Visitor<'de>,
         {
@@ -242,8 +330,11 @@ macro_rules! impl_num {
                     let s = String::from_utf8(bytes)?;
                     s.parse::<$ty>()?
                 }
-                Cow::Borrowed(Value::Int(i)) => *i as $ty,
-                Cow::Owned(Value::Int(i)) => i as $ty,
+                Cow::Borrowed(Value::Int(i)) => {
+                    let $i = *i;
+                    $from_int
+                }
+                Cow::Owned(Value::Int($i)) => $from_int,
                 _ => {
                     let msg = format!("Expected Data or Int, got {:?}", &redis_value);
                     return Err(Error::wrong_value(msg));
@@ -268,23 +359,43 @@ macro_rules! default_deserialize {
     }
 }
 
-impl<'a, 'de> serde::Deserializer<'de> for Deserializer<'a> {
+impl<'a, 'b, 'de> serde::Deserializer<'de> for &'b mut Deserializer<'a> {
     type Error = Error;
 
+    /// Values describe themselves: `Nil` is a unit, `Int` is an `i64`, `Data` is a string when it
+    /// is UTF-8 and bytes otherwise, `Status` and `Okay` are strings, and `Bulk` is a sequence
     #[inline]
-    fn deserialize_any<V>(mut self, visitor: V) -> Result<V::Value>
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        let buf = self.next_bytes()?;
-        match buf {
-            Cow::Borrowed(buf) => visitor.visit_bytes(buf),
-            Cow::Owned(buf) => visitor.visit_byte_buf(buf),
+        match self.peek() {
+            Some(Value::Nil) => {
+                self.next()?;
+                visitor.visit_unit()
+            }
+            Some(Value::Int(_)) => self.deserialize_i64(visitor),
+            Some(Value::Bulk(_)) => self.deserialize_seq(visitor),
+            Some(Value::Status(_)) => self.deserialize_str(visitor),
+            Some(Value::Okay) => {
+                self.next()?;
+                visitor.visit_str("OK")
+            }
+            _ => match self.next_bytes()? {
+                Cow::Borrowed(bytes) => match str::from_utf8(bytes) {
+                    Ok(s) => visitor.visit_str(s),
+                    Err(_) => visitor.visit_bytes(bytes),
+                },
+                Cow::Owned(bytes) => match String::from_utf8(bytes) {
+                    Ok(s) => visitor.visit_string(s),
+                    Err(err) => visitor.visit_byte_buf(err.into_bytes()),
+                },
+            },
         }
     }
 
     #[inline]
-    fn deserialize_string<V>(mut self, visitor: V) -> Result<V::Value>
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
@@ -296,7 +407,7 @@ impl<'a, 'de> serde::Deserializer<'de> for Deserializer<'a> {
     }
 
     #[inline]
-    fn deserialize_str<V>(mut self, visitor: V) -> Result<V::Value>
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
@@ -317,19 +428,43 @@ impl<'a, 'de> serde::Deserializer<'de> for Deserializer<'a> {
     impl_num!(i32, deserialize_i32, visit_i32);
     impl_num!(i64, deserialize_i64, visit_i64);
 
-    impl_num!(f32, deserialize_f32, visit_f32);
-    impl_num!(f64, deserialize_f64, visit_f64);
+    impl_num!(float f32, deserialize_f32, visit_f32);
+    impl_num!(float f64, deserialize_f64, visit_f64);
 
-    default_deserialize!(
-        deserialize_char
-        deserialize_unit
-    );
+    default_deserialize!(deserialize_char);
 
+    /// `Nil` is a unit, as `encode::Serializer` writes it; anything else is read as a string
     #[inline]
-    fn deserialize_bool<V>(mut self, visitor: V) -> Result<V::Value>
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
+        if let Some(Value::Nil) = self.peek() {
+            self.next()?;
+            return visitor.visit_unit();
+        }
+
+        self.deserialize_str(visitor)
+    }
+
+    #[inline]
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if self.lua {
+            let b = match self.peek() {
+                Some(Value::Int(1)) => Some(true),
+                Some(Value::Int(0)) | Some(Value::Nil) => Some(false),
+                _ => None,
+            };
+
+            if let Some(b) = b {
+                self.next()?;
+                return visitor.visit_bool(b);
+            }
+        }
+
         let s = self.read_string()?;
 
         let b = match s.as_ref() {
@@ -355,7 +490,7 @@ impl<'a, 'de> serde::Deserializer<'de> for Deserializer<'a> {
     }
 
     #[inline]
-    fn deserialize_byte_buf<V>(mut self, visitor: V) -> Result<V::Value>
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
@@ -380,32 +515,42 @@ impl<'a, 'de> serde::Deserializer<'de> for Deserializer<'a> {
     }
 
     #[inline]
-    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        self.deserialize_seq(visitor)
+        let values = self.next_bulk()?;
+        visitor.visit_seq(SeqVisitor {
+            iter: CowIter::new(values),
+            index: 0,
+            pad_to: if self.lua { len } else { 0 },
+            lua: self.lua,
+        })
     }
 
     #[inline]
-    fn deserialize_seq<V>(mut self, visitor: V) -> Result<V::Value>
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
         let values = self.next_bulk()?;
         visitor.visit_seq(SeqVisitor {
             iter: CowIter::new(values),
+            index: 0,
+            pad_to: 0,
+            lua: self.lua,
         })
     }
 
     #[inline]
-    fn deserialize_map<V>(mut self, visitor: V) -> Result<V::Value>
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
         let values = self.next_bulk()?;
         visitor.visit_map(MapVisitor {
             iter: CowIter::new(values),
+            lua: self.lua,
         })
     }
 
@@ -440,7 +585,7 @@ impl<'a, 'de> serde::Deserializer<'de> for Deserializer<'a> {
 
     #[inline]
     fn deserialize_enum<V>(
-        mut self,
+        self,
         _enum: &'static str,
         _variants: &'static [&'static str],
         visitor: V,
@@ -451,39 +596,40 @@ impl<'a, 'de> serde::Deserializer<'de> for Deserializer<'a> {
         visitor.visit_enum(EnumVisitor {
             variant: self.next()?,
             content: Cow::Owned(Value::Nil),
+            lua: self.lua,
         })
     }
 
     #[inline]
-    fn deserialize_option<V>(mut self, visitor: V) -> Result<V::Value>
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        let maybe = match self.peek() {
-            Some(v) => match *v {
-                Value::Data(_) => Some(()),
-                Value::Int(_) => Some(()),
-                Value::Nil => None,
-                _ => {
-                    let msg = format!("Expected Data, Int, or Nil");
-                    return Err(Error::wrong_value(msg));
-                }
-            },
-            None => None,
+        let some = match self.peek() {
+            Some(v) => !matches!(*v, Value::Nil),
+            None => false,
         };
 
-        if maybe.is_some() {
+        if some {
             visitor.visit_some(self)
         } else {
+            // Consume the `Nil` so the next value is read after it
+            self.values.next();
             visitor.visit_none()
         }
     }
 
     #[inline]
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
+        if name == SLOT {
+            return visitor
+                .visit_newtype_struct(KeepError(self))
+                .map_err(|Kept(err)| err);
+        }
+
         visitor.visit_newtype_struct(self)
     }
 
@@ -496,8 +642,102 @@ impl<'a, 'de> serde::Deserializer<'de> for Deserializer<'a> {
     }
 }
 
+macro_rules! forward_to_mut {
+    ($($method:ident($($arg:ident: $ty:ty),*))*) => {
+        $(
+            #[inline]
+            fn $method<V>(mut self, $($arg: $ty,)* visitor: V) -> Result<V::Value>
+            where
+                V: de::Visitor<'de>,
+            {
+                (&mut self).$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+/// Deserializes the next value, leaving any further values unread
+impl<'a, 'de> serde::Deserializer<'de> for Deserializer<'a> {
+    type Error = Error;
+
+    forward_to_mut! {
+        deserialize_any()
+        deserialize_bool()
+        deserialize_i8() deserialize_i16() deserialize_i32() deserialize_i64()
+        deserialize_u8() deserialize_u16() deserialize_u32() deserialize_u64()
+        deserialize_f32() deserialize_f64()
+        deserialize_char() deserialize_str() deserialize_string()
+        deserialize_bytes() deserialize_byte_buf()
+        deserialize_option() deserialize_unit()
+        deserialize_unit_struct(name: &'static str)
+        deserialize_newtype_struct(name: &'static str)
+        deserialize_seq()
+        deserialize_tuple(len: usize)
+        deserialize_tuple_struct(name: &'static str, len: usize)
+        deserialize_map()
+        deserialize_struct(name: &'static str, fields: &'static [&'static str])
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+        deserialize_identifier()
+        deserialize_ignored_any()
+    }
+}
+
+/// Iterator that deserializes one `T` per value of a `Deserializer`
+///
+/// Created with `Deserializer::into_iter`. Useful for replies made of several independent values,
+/// such as the results of a pipeline or `EXEC`. Iteration stops after the first error, since the
+/// value that failed may not have been read past.
+#[derive(Debug)]
+pub struct StreamDeserializer<'a, T> {
+    de: Deserializer<'a>,
+    failed: bool,
+    output: PhantomData<T>,
+}
+
+impl<'a, T> StreamDeserializer<'a, T>
+where
+    T: de::DeserializeOwned,
+{
+    pub fn new<V>(values: V) -> Self
+    where
+        V: AsValueVec<'a>,
+    {
+        Deserializer::new(values).into_iter()
+    }
+}
+
+impl<'a, T> Iterator for StreamDeserializer<'a, T>
+where
+    T: de::DeserializeOwned,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        if self.failed {
+            return None;
+        }
+
+        self.de.peek()?;
+        let value = T::deserialize(&mut self.de);
+        self.failed = value.is_err();
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.failed {
+            return (0, Some(0));
+        }
+
+        self.de.values.size_hint()
+    }
+}
+
 struct SeqVisitor<'a> {
     iter: CowIter<'a>,
+    index: usize,
+    /// Yield `Nil` after the last value until this many elements were read
+    pad_to: usize,
+    lua: bool,
 }
 
 impl<'a, 'de> de::SeqAccess<'de> for SeqVisitor<'a> {
@@ -507,19 +747,26 @@ impl<'a, 'de> de::SeqAccess<'de> for SeqVisitor<'a> {
     where
         T: de::DeserializeSeed<'de>,
     {
-        match self.iter.next() {
-            Some(v) => seed.deserialize(Deserializer::new(v)).map(Some),
-            None => Ok(None),
-        }
+        let value = match self.iter.next() {
+            Some(v) => v,
+            None if self.index < self.pad_to => Cow::Owned(Value::Nil),
+            None => return Ok(None),
+        };
+
+        self.index += 1;
+        seed.deserialize(Deserializer::nested(value, self.lua))
+            .map(Some)
     }
 
     fn size_hint(&self) -> Option<usize> {
-        self.iter.size_hint().1
+        let padding = self.pad_to.saturating_sub(self.index);
+        self.iter.size_hint().1.map(|len| len.max(padding))
     }
 }
 
 struct MapVisitor<'a> {
     iter: CowIter<'a>,
+    lua: bool,
 }
 
 impl<'a, 'de> serde::de::MapAccess<'de> for MapVisitor<'a> {
@@ -530,7 +777,9 @@ impl<'a, 'de> serde::de::MapAccess<'de> for MapVisitor<'a> {
         K: de::DeserializeSeed<'de>,
     {
         match self.iter.next() {
-            Some(v) => seed.deserialize(Deserializer::new(v)).map(Some),
+            Some(v) => seed
+                .deserialize(Deserializer::nested(v, self.lua))
+                .map(Some),
             None => Ok(None),
         }
     }
@@ -541,14 +790,19 @@ impl<'a, 'de> serde::de::MapAccess<'de> for MapVisitor<'a> {
         V: de::DeserializeSeed<'de>,
     {
         match self.iter.next() {
-            Some(v) => seed.deserialize(Deserializer::new(v)),
+            Some(v) => seed.deserialize(Deserializer::nested(v, self.lua)),
             None => Err(Error::EndOfStream),
         }
     }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.iter.size_hint().1.map(|len| len / 2)
+    }
 }
 
 struct VariantVisitor<'a> {
     value: Cow<'a, Value>,
+    lua: bool,
 }
 
 impl<'a, 'de> serde::de::VariantAccess<'de> for VariantVisitor<'a> {
@@ -562,7 +816,7 @@ impl<'a, 'de> serde::de::VariantAccess<'de> for VariantVisitor<'a> {
     where
         T: de::DeserializeSeed<'de>,
     {
-        seed.deserialize(Deserializer::new(self.value))
+        seed.deserialize(Deserializer::nested(self.value, self.lua))
     }
 
     fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
@@ -570,7 +824,7 @@ impl<'a, 'de> serde::de::VariantAccess<'de> for VariantVisitor<'a> {
         V: de::Visitor<'de>,
     {
         use serde::Deserializer;
-        let deserializer = self::Deserializer::new(self.value);
+        let deserializer = self::Deserializer::nested(self.value, self.lua);
         deserializer.deserialize_any(visitor)
     }
 
@@ -579,7 +833,7 @@ impl<'a, 'de> serde::de::VariantAccess<'de> for VariantVisitor<'a> {
         V: de::Visitor<'de>,
     {
         use serde::Deserializer;
-        let deserializer = self::Deserializer::new(self.value);
+        let deserializer = self::Deserializer::nested(self.value, self.lua);
         deserializer.deserialize_any(visitor)
     }
 }
@@ -587,6 +841,7 @@ impl<'a, 'de> serde::de::VariantAccess<'de> for VariantVisitor<'a> {
 struct EnumVisitor<'a> {
     variant: Cow<'a, Value>,
     content: Cow<'a, Value>,
+    lua: bool,
 }
 
 impl<'a, 'de> de::EnumAccess<'de> for EnumVisitor<'a> {
@@ -598,10 +853,95 @@ impl<'a, 'de> de::EnumAccess<'de> for EnumVisitor<'a> {
         V: de::DeserializeSeed<'de>,
     {
         Ok((
-            seed.deserialize(Deserializer::new(self.variant))?,
+            seed.deserialize(Deserializer::nested(self.variant, self.lua))?,
             VariantVisitor {
                 value: self.content,
+                lua: self.lua,
             },
         ))
     }
 }
+
+/// The newtype name `pipeline::Slot` deserializes under, to be given a deserializer whose errors
+/// carry the `Error` decoding failed with
+pub(crate) const SLOT: &str = "$serde_redis::Slot";
+
+/// An `Error` passed through code generic over the deserializer, which finds it as the `source`
+#[derive(Debug)]
+struct Kept(Error);
+
+impl error::Error for Kept {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+impl fmt::Display for Kept {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl de::Error for Kept {
+    fn custom<T: Display>(msg: T) -> Self {
+        Kept(Error::custom(msg))
+    }
+
+    fn unknown_variant(variant: &str, expected: &'static [&'static str]) -> Self {
+        Kept(Error::unknown_variant(variant, expected))
+    }
+
+    fn unknown_field(field: &str, expected: &'static [&'static str]) -> Self {
+        Kept(Error::unknown_field(field, expected))
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Kept(Error::missing_field(field))
+    }
+
+    fn duplicate_field(field: &'static str) -> Self {
+        Kept(Error::duplicate_field(field))
+    }
+}
+
+/// Deserializes like the wrapped `Deserializer`, failing with `Kept` errors
+struct KeepError<'b, 'a>(&'b mut Deserializer<'a>);
+
+macro_rules! keep_error {
+    ($($method:ident($($arg:ident: $ty:ty),*))*) => {
+        $(
+            #[inline]
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> ::std::result::Result<V::Value, Kept>
+            where
+                V: de::Visitor<'de>,
+            {
+                self.0.$method($($arg,)* visitor).map_err(Kept)
+            }
+        )*
+    };
+}
+
+impl<'b, 'a, 'de> serde::Deserializer<'de> for KeepError<'b, 'a> {
+    type Error = Kept;
+
+    keep_error! {
+        deserialize_any()
+        deserialize_bool()
+        deserialize_i8() deserialize_i16() deserialize_i32() deserialize_i64()
+        deserialize_u8() deserialize_u16() deserialize_u32() deserialize_u64()
+        deserialize_f32() deserialize_f64()
+        deserialize_char() deserialize_str() deserialize_string()
+        deserialize_bytes() deserialize_byte_buf()
+        deserialize_option() deserialize_unit()
+        deserialize_unit_struct(name: &'static str)
+        deserialize_newtype_struct(name: &'static str)
+        deserialize_seq()
+        deserialize_tuple(len: usize)
+        deserialize_tuple_struct(name: &'static str, len: usize)
+        deserialize_map()
+        deserialize_struct(name: &'static str, fields: &'static [&'static str])
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+        deserialize_identifier()
+        deserialize_ignored_any()
+    }
+}
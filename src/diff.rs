@@ -0,0 +1,108 @@
+use redis::{ToRedisArgs, Value};
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::encode::{self, to_hash_fields};
+
+/// The hash fields that changed between two versions of a value
+///
+/// Produced by [`diff`]. `set` holds fields that are new or whose value changed, and `del` holds
+/// fields that were present in the old version but are `None` or missing in the new one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diff {
+    pub set: Vec<(Vec<u8>, Vec<u8>)>,
+    pub del: Vec<Vec<u8>>,
+}
+
+impl Diff {
+    /// Whether the two versions store the same hash
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty() && self.del.is_empty()
+    }
+
+    /// The `HSET` and `HDEL` commands that move the hash at `key` from the old version to the new
+    ///
+    /// Returns no commands when nothing changed, and at most one of each otherwise.
+    pub fn cmds<K>(&self, key: K) -> Vec<redis::Cmd>
+    where
+        K: ToRedisArgs,
+    {
+        let mut cmds = Vec::new();
+
+        if !self.set.is_empty() {
+            let mut cmd = redis::cmd("HSET");
+            cmd.arg(&key);
+            for (field, value) in &self.set {
+                cmd.arg(field).arg(value);
+            }
+            cmds.push(cmd);
+        }
+
+        if !self.del.is_empty() {
+            let mut cmd = redis::cmd("HDEL");
+            cmd.arg(&key).arg(&self.del);
+            cmds.push(cmd);
+        }
+
+        cmds
+    }
+}
+
+/// Compute the minimal hash update from `old` to `new`
+///
+/// Both values are flattened with `encode::to_hash_fields` and compared field by field, so only
+/// what changed is written. That keeps writes to large hashes small and leaves fields that were
+/// not touched to whoever else is writing them.
+///
+/// ```no_run
+/// # #[macro_use] extern crate serde_derive;
+/// # fn f(con: &mut redis::Connection) -> Result<(), Box<dyn std::error::Error>> {
+/// #[derive(Serialize)]
+/// struct User {
+///     name: String,
+///     email: Option<String>,
+/// }
+///
+/// let old = User { name: "ada".into(), email: Some("ada@example.com".into()) };
+/// let new = User { name: "ada".into(), email: None };
+///
+/// for cmd in serde_redis::diff(&old, &new)?.cmds("user:1") {
+///     cmd.query::<()>(con)?;
+/// }
+/// # Ok(())
+/// # }
+/// # fn main() {}
+/// ```
+pub fn diff<T>(old: &T, new: &T) -> encode::Result<Diff>
+where
+    T: Serialize + ?Sized,
+{
+    let old = to_hash_fields(old)?;
+    let new = to_hash_fields(new)?;
+
+    let old_values: HashMap<&[u8], &Value> = old.iter().map(|(f, v)| (&f[..], v)).collect();
+    let new_values: HashMap<&[u8], &Value> = new.iter().map(|(f, v)| (&f[..], v)).collect();
+
+    let mut diff = Diff::default();
+
+    for (field, value) in &new {
+        if let Value::Data(bytes) = value {
+            if old_values.get(&field[..]) != Some(&value) {
+                diff.set.push((field.clone(), bytes.clone()));
+            }
+        }
+    }
+
+    for (field, value) in &old {
+        if *value == Value::Nil {
+            continue;
+        }
+
+        match new_values.get(&field[..]) {
+            Some(Value::Data(_)) => {}
+            _ => diff.del.push(field.clone()),
+        }
+    }
+
+    Ok(diff)
+}
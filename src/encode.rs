@@ -1 +1,578 @@
+use redis::Value;
+use serde::ser::{self, Serialize};
+use std::error;
+use std::fmt::{self, Display};
+
+/// Error that can be produced during serialization
+#[derive(Debug)]
+pub enum Error {
+    Custom(String),
+    /// The value has no `redis::Value` representation that `Deserializer` could read back
+    UnsupportedType(&'static str),
+    /// A hash was requested but the value is not a struct or map of scalar values
+    ExpectedHash(String),
+}
+
+impl Error {
+    pub fn expected_hash<S>(msg: S) -> Error
+    where
+        S: Into<String>,
+    {
+        Error::ExpectedHash(msg.into())
+    }
+}
+
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+impl error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Custom(ref reason) => write!(f, "CustomError({})", reason),
+            Error::UnsupportedType(ref ty) => write!(f, "cannot serialize {} to redis", ty),
+            Error::ExpectedHash(ref reason) => write!(f, "expected hash: {}", reason),
+        }
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+}
+
+impl From<Error> for redis::RedisError {
+    fn from(err: Error) -> redis::RedisError {
+        redis::RedisError::from((
+            redis::ErrorKind::ClientError,
+            "Value could not be serialized",
+            err.to_string(),
+        ))
+    }
+}
+
+/// Serialize `T` into a `redis::Value`
+///
+/// This is the inverse of `from_redis_value`. Scalars become `Data` holding the same text Redis
+/// would store for them, `None` becomes `Nil`, sequences become `Bulk`, and structs and maps
+/// become a `Bulk` of alternating keys and values in the shape `HGETALL` returns.
+pub fn to_redis_value<T>(value: &T) -> Result<Value>
+where
+    T: Serialize + ?Sized,
+{
+    value.serialize(Serializer)
+}
+
+/// Flatten `T` into the `field value` pairs of a Redis hash
+///
+/// `T` must serialize as a struct or map whose values are scalars. Each value is either `Data`
+/// or, for a field that is `None`, `Nil`. Fields keep their serialization order.
+pub fn to_hash_fields<T>(value: &T) -> Result<Vec<(Vec<u8>, Value)>>
+where
+    T: Serialize + ?Sized,
+{
+    value.serialize(HashSerializer)
+}
+
+/// serializes values into Redis `Value`s
+///
+/// The output mirrors what `Deserializer` reads: anything this produces decodes back into the
+/// type it came from. Enum variants with content have no such representation and are rejected
+/// with `Error::UnsupportedType`.
+#[derive(Debug, Clone, Copy, Default)]
 pub struct Serializer;
+
+macro_rules! impl_num {
+    ($ty:ty, $serialize_method:ident) => {
+        #[inline]
+        fn $serialize_method(self, v: $ty) -> Result<Value> {
+            Ok(Value::Data(v.to_string().into_bytes()))
+        }
+    };
+}
+
+/// Format a float the way Redis writes it
+///
+/// Redis spells infinities `inf` and `-inf`, as Rust does and `str::parse` accepts; only NaN is
+/// spelled differently.
+pub(crate) fn float_to_string<F>(v: F) -> String
+where
+    F: fmt::Display,
+{
+    let s = v.to_string();
+    if s == "NaN" {
+        "nan".to_owned()
+    } else {
+        s
+    }
+}
+
+macro_rules! impl_float {
+    ($ty:ty, $serialize_method:ident) => {
+        #[inline]
+        fn $serialize_method(self, v: $ty) -> Result<Value> {
+            Ok(Value::Data(float_to_string(v).into_bytes()))
+        }
+    };
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = Value;
+    type Error = Error;
+
+    type SerializeSeq = BulkSerializer;
+    type SerializeTuple = BulkSerializer;
+    type SerializeTupleStruct = BulkSerializer;
+    type SerializeTupleVariant = ser::Impossible<Value, Error>;
+    type SerializeMap = BulkSerializer;
+    type SerializeStruct = BulkSerializer;
+    type SerializeStructVariant = ser::Impossible<Value, Error>;
+
+    #[inline]
+    fn serialize_bool(self, v: bool) -> Result<Value> {
+        Ok(Value::Data(if v { b"1".to_vec() } else { b"0".to_vec() }))
+    }
+
+    impl_num!(i8, serialize_i8);
+    impl_num!(i16, serialize_i16);
+    impl_num!(i32, serialize_i32);
+    impl_num!(i64, serialize_i64);
+
+    impl_num!(u8, serialize_u8);
+    impl_num!(u16, serialize_u16);
+    impl_num!(u32, serialize_u32);
+    impl_num!(u64, serialize_u64);
+
+    impl_float!(f32, serialize_f32);
+    impl_float!(f64, serialize_f64);
+
+    #[inline]
+    fn serialize_char(self, v: char) -> Result<Value> {
+        Ok(Value::Data(v.to_string().into_bytes()))
+    }
+
+    #[inline]
+    fn serialize_str(self, v: &str) -> Result<Value> {
+        Ok(Value::Data(v.as_bytes().to_vec()))
+    }
+
+    #[inline]
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value> {
+        Ok(Value::Data(v.to_vec()))
+    }
+
+    #[inline]
+    fn serialize_none(self) -> Result<Value> {
+        Ok(Value::Nil)
+    }
+
+    #[inline]
+    fn serialize_some<T>(self, value: &T) -> Result<Value>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_unit(self) -> Result<Value> {
+        Ok(Value::Nil)
+    }
+
+    #[inline]
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value> {
+        self.serialize_unit()
+    }
+
+    #[inline]
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value> {
+        self.serialize_str(variant)
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Value>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Value>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(Error::UnsupportedType("newtype variant"))
+    }
+
+    #[inline]
+    fn serialize_seq(self, len: Option<usize>) -> Result<BulkSerializer> {
+        Ok(BulkSerializer::with_capacity(len.unwrap_or(0)))
+    }
+
+    #[inline]
+    fn serialize_tuple(self, len: usize) -> Result<BulkSerializer> {
+        self.serialize_seq(Some(len))
+    }
+
+    #[inline]
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<BulkSerializer> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(Error::UnsupportedType("tuple variant"))
+    }
+
+    #[inline]
+    fn serialize_map(self, len: Option<usize>) -> Result<BulkSerializer> {
+        Ok(BulkSerializer::with_capacity(len.unwrap_or(0) * 2))
+    }
+
+    #[inline]
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<BulkSerializer> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(Error::UnsupportedType("struct variant"))
+    }
+}
+
+/// Collects sequence elements, or alternating keys and values, into a `Bulk`
+#[derive(Debug)]
+pub struct BulkSerializer {
+    values: Vec<Value>,
+}
+
+impl BulkSerializer {
+    fn with_capacity(capacity: usize) -> Self {
+        BulkSerializer {
+            values: Vec::with_capacity(capacity),
+        }
+    }
+
+    fn push<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.values.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for BulkSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value> {
+        Ok(Value::Bulk(self.values))
+    }
+}
+
+impl ser::SerializeTuple for BulkSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value> {
+        Ok(Value::Bulk(self.values))
+    }
+}
+
+impl ser::SerializeTupleStruct for BulkSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value> {
+        Ok(Value::Bulk(self.values))
+    }
+}
+
+impl ser::SerializeMap for BulkSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(key)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value> {
+        Ok(Value::Bulk(self.values))
+    }
+}
+
+impl ser::SerializeStruct for BulkSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.values.push(Value::Data(key.as_bytes().to_vec()));
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value> {
+        Ok(Value::Bulk(self.values))
+    }
+}
+
+/// Serializes a struct or map into hash `field value` pairs, rejecting everything else
+struct HashSerializer;
+
+macro_rules! reject {
+    ($($method:ident($($arg:ty),*) => $what:expr;)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<Self::Ok> {
+                Err(Error::expected_hash(concat!("got ", $what)))
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for HashSerializer {
+    type Ok = Vec<(Vec<u8>, Value)>;
+    type Error = Error;
+
+    type SerializeSeq = ser::Impossible<Self::Ok, Error>;
+    type SerializeTuple = ser::Impossible<Self::Ok, Error>;
+    type SerializeTupleStruct = ser::Impossible<Self::Ok, Error>;
+    type SerializeTupleVariant = ser::Impossible<Self::Ok, Error>;
+    type SerializeMap = HashFieldsSerializer;
+    type SerializeStruct = HashFieldsSerializer;
+    type SerializeStructVariant = ser::Impossible<Self::Ok, Error>;
+
+    reject! {
+        serialize_bool(bool) => "bool";
+        serialize_i8(i8) => "integer";
+        serialize_i16(i16) => "integer";
+        serialize_i32(i32) => "integer";
+        serialize_i64(i64) => "integer";
+        serialize_u8(u8) => "integer";
+        serialize_u16(u16) => "integer";
+        serialize_u32(u32) => "integer";
+        serialize_u64(u64) => "integer";
+        serialize_f32(f32) => "float";
+        serialize_f64(f64) => "float";
+        serialize_char(char) => "char";
+        serialize_str(&str) => "string";
+        serialize_bytes(&[u8]) => "bytes";
+        serialize_none() => "none";
+        serialize_unit() => "unit";
+        serialize_unit_struct(&'static str) => "unit struct";
+        serialize_unit_variant(&'static str, u32, &'static str) => "enum variant";
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(Error::expected_hash("got enum variant"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(Error::expected_hash("got sequence"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(Error::expected_hash("got tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(Error::expected_hash("got tuple struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(Error::expected_hash("got enum variant"))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<HashFieldsSerializer> {
+        Ok(HashFieldsSerializer::new(len.unwrap_or(0)))
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<HashFieldsSerializer> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(Error::expected_hash("got enum variant"))
+    }
+}
+
+/// Collects the `field value` pairs of a struct or map, rejecting values that are not scalars
+pub(crate) struct HashFieldsSerializer {
+    fields: Vec<(Vec<u8>, Value)>,
+    key: Option<Vec<u8>>,
+}
+
+impl HashFieldsSerializer {
+    pub(crate) fn new(len: usize) -> Self {
+        HashFieldsSerializer {
+            fields: Vec::with_capacity(len),
+            key: None,
+        }
+    }
+
+    fn push<T>(&mut self, field: Vec<u8>, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        match value.serialize(Serializer)? {
+            value @ Value::Data(_) | value @ Value::Nil => {
+                self.fields.push((field, value));
+                Ok(())
+            }
+            v => {
+                let msg = format!(
+                    "field {:?} must be scalar, got {:?}",
+                    String::from_utf8_lossy(&field),
+                    v
+                );
+                Err(Error::expected_hash(msg))
+            }
+        }
+    }
+}
+
+impl ser::SerializeMap for HashFieldsSerializer {
+    type Ok = Vec<(Vec<u8>, Value)>;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        match key.serialize(Serializer)? {
+            Value::Data(key) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            v => {
+                let msg = format!("field names must be scalar, got {:?}", v);
+                Err(Error::expected_hash(msg))
+            }
+        }
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        let field = self
+            .key
+            .take()
+            .ok_or_else(|| Error::Custom("serialize_value called before serialize_key".into()))?;
+        self.push(field, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(self.fields)
+    }
+}
+
+impl ser::SerializeStruct for HashFieldsSerializer {
+    type Ok = Vec<(Vec<u8>, Value)>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(key.as_bytes().to_vec(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(self.fields)
+    }
+}
@@ -0,0 +1,117 @@
+use redis::{ConnectionLike, RedisResult, ToRedisArgs, Value};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::encode::to_hash_fields;
+use crate::from_redis_value;
+
+/// Typed commands for any `redis::ConnectionLike`
+///
+/// Each method sends one command, then decodes the reply with `from_redis_value` (or encodes the
+/// argument with the serializer), so call sites don't need the intermediate `redis::Value`.
+///
+/// ```no_run
+/// # #[macro_use] extern crate serde_derive;
+/// use serde_redis::RedisSerdeExt;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct User {
+///     name: String,
+///     visits: u32,
+/// }
+///
+/// # fn f(con: &mut redis::Connection) -> redis::RedisResult<()> {
+/// con.hset_struct("user:1", &User { name: "ada".into(), visits: 1 })?;
+/// let user: User = con.hget_struct("user:1")?;
+/// # Ok(())
+/// # }
+/// # fn main() {}
+/// ```
+pub trait RedisSerdeExt: ConnectionLike + Sized {
+    /// `HGETALL key`, decoded into `T`
+    fn hget_struct<K, T>(&mut self, key: K) -> RedisResult<T>
+    where
+        K: ToRedisArgs,
+        T: DeserializeOwned,
+    {
+        let value: Value = redis::cmd("HGETALL").arg(key).query(self)?;
+        Ok(from_redis_value(value)?)
+    }
+
+    /// `HSET key field value...` with the fields of `value`
+    ///
+    /// Fields that are `None` are left out; they are not removed from an existing hash. Use
+    /// `diff` to also delete them.
+    fn hset_struct<K, T>(&mut self, key: K, value: &T) -> RedisResult<()>
+    where
+        K: ToRedisArgs,
+        T: Serialize + ?Sized,
+    {
+        let mut cmd = redis::cmd("HSET");
+        cmd.arg(key);
+
+        let mut empty = true;
+        for (field, value) in to_hash_fields(value)? {
+            if let Value::Data(bytes) = value {
+                cmd.arg(field).arg(bytes);
+                empty = false;
+            }
+        }
+
+        if empty {
+            return Ok(());
+        }
+
+        cmd.query(self)
+    }
+
+    /// `GET key`, decoded into `T`
+    ///
+    /// Use `Option<T>` to handle missing keys.
+    fn get_as<K, T>(&mut self, key: K) -> RedisResult<T>
+    where
+        K: ToRedisArgs,
+        T: DeserializeOwned,
+    {
+        let value: Value = redis::cmd("GET").arg(key).query(self)?;
+        Ok(from_redis_value(value)?)
+    }
+
+    /// `MGET key...`, decoded into one `T` per key
+    ///
+    /// Use `Option<T>` to handle missing keys.
+    fn mget_as<K, T>(&mut self, keys: K) -> RedisResult<Vec<T>>
+    where
+        K: ToRedisArgs,
+        T: DeserializeOwned,
+    {
+        let value: Value = redis::cmd("MGET").arg(keys).query(self)?;
+        Ok(from_redis_value(value)?)
+    }
+
+    /// `LRANGE key start stop`, decoded into one `T` per element
+    fn lrange_as<K, T>(&mut self, key: K, start: isize, stop: isize) -> RedisResult<Vec<T>>
+    where
+        K: ToRedisArgs,
+        T: DeserializeOwned,
+    {
+        let value: Value = redis::cmd("LRANGE")
+            .arg(key)
+            .arg(start)
+            .arg(stop)
+            .query(self)?;
+        Ok(from_redis_value(value)?)
+    }
+
+    /// `SMEMBERS key`, decoded into one `T` per member
+    fn smembers_as<K, T>(&mut self, key: K) -> RedisResult<Vec<T>>
+    where
+        K: ToRedisArgs,
+        T: DeserializeOwned,
+    {
+        let value: Value = redis::cmd("SMEMBERS").arg(key).query(self)?;
+        Ok(from_redis_value(value)?)
+    }
+}
+
+impl<C> RedisSerdeExt for C where C: ConnectionLike {}
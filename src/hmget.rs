@@ -0,0 +1,288 @@
+//! Positional decoding of `HMGET` replies
+//!
+//! `HMGET` returns only the values, in the order the fields were requested. The helpers here ask
+//! a struct for its field list (the `fields` slice serde passes to `deserialize_struct`), build
+//! the command from it, and zip the reply back up with those names before decoding.
+//!
+//! ```no_run
+//! # #[macro_use] extern crate serde_derive;
+//! # fn f(con: &mut redis::Connection) -> Result<(), Box<dyn std::error::Error>> {
+//! #[derive(Deserialize)]
+//! struct Summary {
+//!     name: String,
+//!     visits: Option<u64>,
+//! }
+//!
+//! let reply: redis::Value = serde_redis::hmget::cmd::<Summary, _>("user:1")?.query(con)?;
+//! let summary: Summary = serde_redis::hmget::from_redis_value(reply)?;
+//! # Ok(())
+//! # }
+//! # fn main() {}
+//! ```
+use redis::{ToRedisArgs, Value};
+use serde::de::{self, Deserialize, IntoDeserializer, Visitor};
+use std::borrow::Cow;
+use std::{error, fmt};
+
+use crate::decode::{Error, Result};
+use crate::IntoCow;
+
+/// Returns the field names of struct `T`, in declaration order
+///
+/// These are the names the fields are stored under, so `#[serde(rename)]` is honored and
+/// `#[serde(skip)]` fields are left out. Types that do not deserialize as a struct, such as maps
+/// or structs using `#[serde(flatten)]`, return `Error::DeserializeNotSupported`.
+///
+/// Structs with `#[serde(alias)]` fields are rejected with `Error::Custom`. Serde lists aliases
+/// among the field names without marking them, so they cannot be left out, and fetching both
+/// names of a field would fail to decode with a duplicate field error.
+pub fn fields<'de, T>() -> Result<&'static [&'static str]>
+where
+    T: Deserialize<'de>,
+{
+    let mut fields = None;
+    let _ = T::deserialize(FieldsDeserializer {
+        fields: &mut fields,
+    });
+    let fields = fields.ok_or(Error::DeserializeNotSupported)?;
+
+    // Without aliases, every name is a field that serde also knows by its index
+    let known = (0..fields.len())
+        .take_while(|&index| is_field_index::<T>(index))
+        .count();
+    if known != fields.len() {
+        let msg = format!(
+            "{} names {:?} for {} fields; `#[serde(alias)]` is not supported",
+            fields.len(),
+            fields,
+            known
+        );
+        return Err(Error::Custom(msg));
+    }
+
+    Ok(fields)
+}
+
+/// Whether `T` decodes a value for the field at `index` rather than ignoring it
+fn is_field_index<'de, T>(index: usize) -> bool
+where
+    T: Deserialize<'de>,
+{
+    matches!(
+        T::deserialize(IndexProbe(Some(index as u64))),
+        Err(Probe::Field)
+    )
+}
+
+/// Build an `HMGET key field...` command requesting every field of `T`
+pub fn cmd<'de, T, K>(key: K) -> Result<redis::Cmd>
+where
+    T: Deserialize<'de>,
+    K: ToRedisArgs,
+{
+    let mut cmd = redis::cmd("HMGET");
+    cmd.arg(key).arg(fields::<T>()?);
+
+    Ok(cmd)
+}
+
+/// Decode an `HMGET` reply for the fields of `T` into `T`
+///
+/// Values are matched to names by position. A `Nil` value means the field is absent from the
+/// hash, so it decodes the same way a missing key in an `HGETALL` reply would.
+pub fn from_redis_value<'a, 'de, T, RV>(rv: RV) -> Result<T>
+where
+    T: Deserialize<'de>,
+    RV: IntoCow<'a>,
+{
+    let names = fields::<T>()?;
+    let values = match rv.into_cow() {
+        Cow::Owned(Value::Bulk(values)) => values,
+        Cow::Borrowed(Value::Bulk(values)) => values.clone(),
+        v => {
+            let msg = format!("expected bulk but got {:?}", v);
+            return Err(Error::wrong_value(msg));
+        }
+    };
+
+    if values.len() != names.len() {
+        let msg = format!(
+            "expected {} HMGET values but got {}",
+            names.len(),
+            values.len()
+        );
+        return Err(Error::wrong_value(msg));
+    }
+
+    let mut pairs = Vec::with_capacity(values.len() * 2);
+    for (name, value) in names.iter().zip(values) {
+        if value != Value::Nil {
+            pairs.push(Value::Data(name.as_bytes().to_vec()));
+            pairs.push(value);
+        }
+    }
+
+    crate::from_redis_value(Value::Bulk(pairs))
+}
+
+/// Deserializer that records the `fields` slice handed to `deserialize_struct` and then bails
+struct FieldsDeserializer<'f> {
+    fields: &'f mut Option<&'static [&'static str]>,
+}
+
+impl<'f, 'de> de::Deserializer<'de> for FieldsDeserializer<'f> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::DeserializeNotSupported)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        *self.fields = Some(fields);
+        Err(Error::DeserializeNotSupported)
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+/// Outcome of feeding a struct one field index, carried as the error that stops decoding
+#[derive(Debug)]
+enum Probe {
+    /// The struct decoded a value for the field
+    Field,
+    /// The struct ignored the field or rejected the index
+    NotField,
+}
+
+impl de::Error for Probe {
+    fn custom<T>(_msg: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        Probe::NotField
+    }
+}
+
+impl fmt::Display for Probe {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl error::Error for Probe {}
+
+/// Deserializer presenting a struct with a single field given by index
+///
+/// Derived structs map a field index past their last field to an ignored key, which they skip
+/// with `deserialize_ignored_any`, or reject when they deny unknown fields.
+struct IndexProbe(Option<u64>);
+
+impl<'de> de::Deserializer<'de> for IndexProbe {
+    type Error = Probe;
+
+    fn deserialize_any<V>(self, _visitor: V) -> ::std::result::Result<V::Value, Probe>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Probe::NotField)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> ::std::result::Result<V::Value, Probe>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> ::std::result::Result<V::Value, Probe>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+impl<'de> de::MapAccess<'de> for IndexProbe {
+    type Error = Probe;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> ::std::result::Result<Option<K::Value>, Probe>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        match self.0.take() {
+            Some(index) => seed.deserialize(index.into_deserializer()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> ::std::result::Result<V::Value, Probe>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(ValueProbe)
+    }
+}
+
+/// Deserializer for the probed field's value, telling a decoded field from an ignored one
+struct ValueProbe;
+
+impl<'de> de::Deserializer<'de> for ValueProbe {
+    type Error = Probe;
+
+    fn deserialize_any<V>(self, _visitor: V) -> ::std::result::Result<V::Value, Probe>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Probe::Field)
+    }
+
+    fn deserialize_ignored_any<V>(self, _visitor: V) -> ::std::result::Result<V::Value, Probe>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Probe::NotField)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier
+    }
+}
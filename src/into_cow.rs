@@ -20,3 +20,9 @@ impl IntoCow<'static> for Value {
         Cow::Owned(self)
     }
 }
+
+impl<'a> IntoCow<'a> for Cow<'a, Value> {
+    fn into_cow(self) -> Cow<'a, Value> {
+        self
+    }
+}
@@ -0,0 +1,232 @@
+//! Converting between `redis::Value` and `serde_json::Value`
+//!
+//! Meant for logging replies, debugging endpoints and admin tools, where a reply should be shown
+//! as JSON without a Rust type to decode it into. [`to_json`] reads a value through the
+//! self-describing `deserialize_any` path:
+//!
+//! - `Data` is a string, or `{"base64": "..."}` if it is not UTF-8
+//! - `Int` is a number, `Nil` is `null`, and `Status` and `Okay` are strings
+//! - `Bulk` is an array, or in [`Mode::Map`] an object when it holds field/value pairs
+//!
+//! [`from_json`] goes the other way, giving what `to_redis_value` does for the same JSON except
+//! that `{"base64": "..."}` objects become the `Data` they encode. [`Seed`] reads JSON from other
+//! deserializers, such as `resp::Deserializer`, the same way.
+//!
+//! ```
+//! use redis::Value;
+//! use serde_json::json;
+//! use serde_redis::json::{self, Mode};
+//!
+//! let reply = Value::Bulk(vec![
+//!     Value::Data(b"avatar".to_vec()),
+//!     Value::Data(vec![0xff, 0x00]),
+//!     Value::Data(b"name".to_vec()),
+//!     Value::Data(b"ada".to_vec()),
+//! ]);
+//!
+//! let value = json::to_json(&reply, Mode::Map).unwrap();
+//! assert_eq!(json!({ "name": "ada", "avatar": { "base64": "/wA=" } }), value);
+//! assert_eq!(reply, json::from_json(&value).unwrap());
+//! ```
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use redis::Value;
+use serde::de::{self, DeserializeSeed};
+use serde_json::{Map, Number, Value as Json};
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::{decode, encode, Deserializer};
+
+/// The key of the object holding data that is not UTF-8
+const BASE64: &str = "base64";
+
+/// How `Bulk` values are converted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    /// Every `Bulk` is an array
+    #[default]
+    Array,
+    /// A `Bulk` of alternating fields and values, the shape `HGETALL` replies with, is an object;
+    /// other `Bulk` values are arrays
+    ///
+    /// Applies at every level. A `Bulk` is only an object if it has an even number of elements,
+    /// every field is a string and no field repeats, so a list of strings of even length is also
+    /// read as an object.
+    Map,
+}
+
+/// Convert a `redis::Value` into JSON
+pub fn to_json(value: &Value, mode: Mode) -> decode::Result<Json> {
+    Seed(mode).deserialize(Deserializer::new(value))
+}
+
+/// Convert JSON into the `redis::Value` the serializer would write for it
+///
+/// Objects of the form `{"base64": "..."}` holding valid base64 become the `Data` they encode,
+/// so `to_json` is reversed for non UTF-8 data. A hash whose only field is `base64`, converted
+/// in `Mode::Map`, has the same shape and is read back as `Data` too.
+///
+/// Fields are written in the order `serde_json::Map` keeps them, which is sorted unless its
+/// `preserve_order` feature is enabled.
+pub fn from_json(value: &Json) -> encode::Result<Value> {
+    match *value {
+        Json::Array(ref values) => values
+            .iter()
+            .map(from_json)
+            .collect::<encode::Result<_>>()
+            .map(Value::Bulk),
+        Json::Object(ref map) => {
+            if let Some(bytes) = base64_data(map) {
+                return Ok(Value::Data(bytes));
+            }
+
+            let mut values = Vec::with_capacity(map.len() * 2);
+            for (field, value) in map {
+                values.push(Value::Data(field.as_bytes().to_vec()));
+                values.push(from_json(value)?);
+            }
+            Ok(Value::Bulk(values))
+        }
+        ref scalar => encode::to_redis_value(scalar),
+    }
+}
+
+fn base64_data(map: &Map<String, Json>) -> Option<Vec<u8>> {
+    match (map.len(), map.get(BASE64)) {
+        (1, Some(Json::String(encoded))) => STANDARD.decode(encoded).ok(),
+        _ => None,
+    }
+}
+
+/// Reads JSON from any of this crate's deserializers, converting `Bulk` values as in `Mode`
+///
+/// ```
+/// use serde::de::DeserializeSeed;
+/// use serde_redis::json::{Mode, Seed};
+/// use serde_redis::resp;
+///
+/// let mut de = resp::Deserializer::from_slice(b"*2\r\n:1\r\n$-1\r\n");
+/// let value = Seed(Mode::Array).deserialize(&mut de).unwrap();
+///
+/// assert_eq!(serde_json::json!([1, null]), value);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Seed(pub Mode);
+
+impl<'de> DeserializeSeed<'de> for Seed {
+    type Value = Json;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Json, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> de::Visitor<'de> for Seed {
+    type Value = Json;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any Redis value")
+    }
+
+    fn visit_unit<E>(self) -> Result<Json, E> {
+        Ok(Json::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<Json, E> {
+        Ok(Json::Null)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Json, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.deserialize(deserializer)
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Json, E> {
+        Ok(Json::Bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Json, E> {
+        Ok(Json::Number(v.into()))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Json, E> {
+        Ok(Json::Number(v.into()))
+    }
+
+    /// Infinities and NaN, which JSON has no numbers for, are strings spelled as Redis does
+    fn visit_f64<E>(self, v: f64) -> Result<Json, E> {
+        Ok(match Number::from_f64(v) {
+            Some(number) => Json::Number(number),
+            None if v.is_nan() => Json::String("nan".to_owned()),
+            None if v > 0.0 => Json::String("inf".to_owned()),
+            None => Json::String("-inf".to_owned()),
+        })
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Json, E> {
+        Ok(Json::String(v.to_owned()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Json, E> {
+        Ok(Json::String(v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Json, E> {
+        let mut map = Map::new();
+        map.insert(BASE64.to_owned(), Json::String(STANDARD.encode(v)));
+        Ok(Json::Object(map))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Json, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(value) = seq.next_element_seed(self)? {
+            values.push(value);
+        }
+
+        if self.0 == Mode::Map && is_pairs(&values) {
+            let mut map = Map::new();
+            let mut values = values.into_iter();
+            while let (Some(Json::String(field)), Some(value)) = (values.next(), values.next()) {
+                map.insert(field, value);
+            }
+            return Ok(Json::Object(map));
+        }
+
+        Ok(Json::Array(values))
+    }
+
+    /// RESP3 maps are objects in either mode, with fields that are not strings written as JSON
+    fn visit_map<A>(self, mut map: A) -> Result<Json, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let mut object = Map::new();
+        while let Some(field) = map.next_key_seed(self)? {
+            let field = match field {
+                Json::String(field) => field,
+                field => field.to_string(),
+            };
+            object.insert(field, map.next_value_seed(self)?);
+        }
+
+        Ok(Json::Object(object))
+    }
+}
+
+/// Whether `values` alternate unique string fields and values
+fn is_pairs(values: &[Json]) -> bool {
+    let mut fields = HashSet::with_capacity(values.len() / 2);
+    values.chunks(2).all(|pair| match *pair {
+        [Json::String(ref field), _] => fields.insert(field),
+        _ => false,
+    })
+}
@@ -0,0 +1,174 @@
+//! Keyspace notification events
+//!
+//! With `notify-keyspace-events` enabled, Redis publishes every change twice: on
+//! `__keyspace@<db>__:<key>` with the operation as payload, and on `__keyevent@<db>__:<op>` with
+//! the key as payload. [`from_msg`] turns a message from either channel into the same
+//! [`KeyEvent`].
+//!
+//! ```no_run
+//! # fn f(con: &mut redis::Connection) -> Result<(), Box<dyn std::error::Error>> {
+//! use serde_redis::keyspace::{self, Op};
+//!
+//! let mut sub = con.as_pubsub();
+//! sub.psubscribe("__keyevent@*__:*")?;
+//! loop {
+//!     let event = keyspace::from_msg(&sub.get_message()?)?;
+//!     if event.op == Op::Expired {
+//!         println!("{} expired in db {}", String::from_utf8_lossy(&event.key), event.db);
+//!     }
+//! }
+//! # }
+//! # fn main() {}
+//! ```
+use redis::{Msg, Value};
+use serde::Deserialize;
+
+use crate::decode::{Error, Result};
+
+const KEYSPACE_PREFIX: &[u8] = b"__keyspace@";
+const KEYEVENT_PREFIX: &[u8] = b"__keyevent@";
+
+/// A change to a key, from either notification channel
+///
+/// Keys are binary safe in Redis, so `key` holds the bytes of the key as they were published.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyEvent {
+    pub db: u32,
+    pub key: Vec<u8>,
+    pub op: Op,
+}
+
+impl KeyEvent {
+    /// Parse a notification from its channel name and payload
+    pub fn parse<C>(channel: C, payload: &[u8]) -> Result<KeyEvent>
+    where
+        C: AsRef<[u8]>,
+    {
+        let channel = channel.as_ref();
+        let (db, rest, keyspace) = if let Some(rest) = channel.strip_prefix(KEYSPACE_PREFIX) {
+            split_db(rest).map(|(db, rest)| (db, rest, true))?
+        } else if let Some(rest) = channel.strip_prefix(KEYEVENT_PREFIX) {
+            split_db(rest).map(|(db, rest)| (db, rest, false))?
+        } else {
+            return Err(Error::wrong_value(format!(
+                "not a keyspace notification channel: {}",
+                String::from_utf8_lossy(channel)
+            )));
+        };
+
+        let (key, op) = if keyspace {
+            (rest, payload)
+        } else {
+            (payload, rest)
+        };
+
+        let op = crate::from_redis_value(Value::Data(op.to_vec()))?;
+
+        Ok(KeyEvent {
+            db,
+            key: key.to_vec(),
+            op,
+        })
+    }
+}
+
+/// Decode a keyspace or keyevent notification message
+pub fn from_msg(msg: &Msg) -> Result<KeyEvent> {
+    let channel: Vec<u8> = msg
+        .get_channel()
+        .map_err(|err| Error::wrong_value(err.to_string()))?;
+
+    KeyEvent::parse(channel, msg.get_payload_bytes())
+}
+
+/// Split `<db>__:<rest>` from a channel name after its prefix
+fn split_db(s: &[u8]) -> Result<(u32, &[u8])> {
+    let end = s
+        .windows(3)
+        .position(|window| window == b"__:")
+        .ok_or_else(|| {
+            let msg = format!(
+                "malformed notification channel: {}",
+                String::from_utf8_lossy(s)
+            );
+            Error::wrong_value(msg)
+        })?;
+    let db = std::str::from_utf8(&s[..end])
+        .ok()
+        .and_then(|db| db.parse().ok())
+        .ok_or_else(|| {
+            let msg = format!("invalid database: {}", String::from_utf8_lossy(&s[..end]));
+            Error::wrong_value(msg)
+        })?;
+
+    Ok((db, &s[end + 3..]))
+}
+
+/// The operation that triggered a notification, as named by Redis
+///
+/// Events this crate does not know decode as `Other`, holding the name Redis sent.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Op {
+    Del,
+    Expire,
+    Expired,
+    Evicted,
+    Persist,
+    New,
+    RenameFrom,
+    RenameTo,
+    MoveFrom,
+    MoveTo,
+    CopyTo,
+    Restore,
+    Sortstore,
+    Set,
+    Setrange,
+    Incrby,
+    Incrbyfloat,
+    Append,
+    Lpush,
+    Rpush,
+    Lpop,
+    Rpop,
+    Linsert,
+    Lset,
+    Lrem,
+    Ltrim,
+    Hset,
+    Hincrby,
+    Hincrbyfloat,
+    Hdel,
+    Sadd,
+    Srem,
+    Spop,
+    Sinterstore,
+    Sunionstore,
+    Sdiffstore,
+    Zadd,
+    Zincr,
+    Zrem,
+    Zremrangebyscore,
+    Zremrangebyrank,
+    Zremrangebylex,
+    Zinterstore,
+    Zunionstore,
+    Zdiffstore,
+    Xadd,
+    Xtrim,
+    Xdel,
+    Xsetid,
+    #[serde(rename = "xgroup-create")]
+    XgroupCreate,
+    #[serde(rename = "xgroup-createconsumer")]
+    XgroupCreateconsumer,
+    #[serde(rename = "xgroup-delconsumer")]
+    XgroupDelconsumer,
+    #[serde(rename = "xgroup-destroy")]
+    XgroupDestroy,
+    #[serde(rename = "xgroup-setid")]
+    XgroupSetid,
+    #[serde(untagged)]
+    Other(String),
+}
@@ -1,13 +1,44 @@
 // `encode` and `decode` are used instead of `ser` and `de` to avoid confusion with the serder
 // Serializer and Deserializer traits which occupy a similar namespace.
+#[cfg(feature = "aio")]
+mod aio;
 mod cow_iter;
 pub mod decode;
+mod diff;
 pub mod encode;
+mod ext;
+pub mod hmget;
 mod into_cow;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod keyspace;
+mod macros;
+pub mod mass_insert;
+pub mod pairs;
+pub mod pipeline;
+pub mod pubsub;
+pub mod resp;
+pub mod schema;
+pub mod script;
+pub mod stream;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod transaction;
 
-pub use crate::decode::Deserializer;
+#[cfg(feature = "aio")]
+pub use crate::aio::AsyncRedisSerdeExt;
+pub use crate::decode::{Deserializer, StreamDeserializer};
+pub use crate::diff::{diff, Diff};
 pub use crate::encode::Serializer;
+pub use crate::ext::RedisSerdeExt;
 pub use crate::into_cow::IntoCow;
+pub use crate::pairs::Pairs;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::macros::Scalar;
+    pub use redis::Value;
+}
 
 /// Use serde Deserialize to build `T` from a `redis::Value`
 pub fn from_redis_value<'a, 'de, T, RV>(rv: RV) -> decode::Result<T>
@@ -15,8 +46,18 @@ where
     T: serde::de::Deserialize<'de>,
     RV: IntoCow<'a>,
 {
-    let value = rv.into_cow();
-    serde::de::Deserialize::deserialize(Deserializer::new(value))
+    let mut de = Deserializer::new(rv.into_cow());
+    let value = serde::de::Deserialize::deserialize(&mut de)?;
+    de.end()?;
+    Ok(value)
+}
+
+/// Use serde Serialize to build a `redis::Value` from `T`
+pub fn to_redis_value<T>(value: &T) -> encode::Result<redis::Value>
+where
+    T: serde::ser::Serialize + ?Sized,
+{
+    encode::to_redis_value(value)
 }
 
 pub trait RedisDeserialize<'de, T>
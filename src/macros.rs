@@ -0,0 +1,150 @@
+use redis::Value;
+
+/// Build a `redis::Value` with a JSON-like syntax
+///
+/// - `nil` is `Nil`, `ok` is `Okay` and `status("PONG")` is `Status`
+/// - `[a, b, c]` is a `Bulk` of its elements
+/// - `{ "field": value, .. }` is a `Bulk` of alternating fields and values, the shape `HGETALL`
+///   replies with
+/// - strings and byte strings are `Data`, integers are `Int` and `None` is `Nil`; any other
+///   expression may be used if it is one of these or a `redis::Value`
+///
+/// ```
+/// use redis::Value;
+/// use serde_redis::redis_value;
+///
+/// let value = redis_value!(["a", 1, nil, ok, status("PONG"), ["nested"], { "field": b"value" }]);
+///
+/// assert_eq!(
+///     Value::Bulk(vec![
+///         Value::Data(b"a".to_vec()),
+///         Value::Int(1),
+///         Value::Nil,
+///         Value::Okay,
+///         Value::Status("PONG".to_owned()),
+///         Value::Bulk(vec![Value::Data(b"nested".to_vec())]),
+///         Value::Bulk(vec![Value::Data(b"field".to_vec()), Value::Data(b"value".to_vec())]),
+///     ]),
+///     value
+/// );
+/// ```
+#[macro_export]
+macro_rules! redis_value {
+    // Elements of an array, munched one at a time into `[$elems,]`
+    (@array [$($elems:expr,)*]) => {
+        ::std::vec![$($elems,)*]
+    };
+    (@array [$($elems:expr,)*] status($status:expr) $(, $($rest:tt)*)?) => {
+        $crate::redis_value!(@array [$($elems,)* $crate::redis_value!(status($status)),] $($($rest)*)?)
+    };
+    (@array [$($elems:expr,)*] $value:tt $(, $($rest:tt)*)?) => {
+        $crate::redis_value!(@array [$($elems,)* $crate::redis_value!($value),] $($($rest)*)?)
+    };
+    (@array [$($elems:expr,)*] $value:expr $(, $($rest:tt)*)?) => {
+        $crate::redis_value!(@array [$($elems,)* $crate::redis_value!($value),] $($($rest)*)?)
+    };
+
+    // Fields and values of an object, flattened into pairs
+    (@object [$($elems:expr,)*]) => {
+        ::std::vec![$($elems,)*]
+    };
+    (@object [$($elems:expr,)*] $field:tt : status($status:expr) $(, $($rest:tt)*)?) => {
+        $crate::redis_value!(@object [
+            $($elems,)* $crate::redis_value!($field), $crate::redis_value!(status($status)),
+        ] $($($rest)*)?)
+    };
+    (@object [$($elems:expr,)*] $field:tt : $value:tt $(, $($rest:tt)*)?) => {
+        $crate::redis_value!(@object [
+            $($elems,)* $crate::redis_value!($field), $crate::redis_value!($value),
+        ] $($($rest)*)?)
+    };
+    (@object [$($elems:expr,)*] $field:tt : $value:expr $(, $($rest:tt)*)?) => {
+        $crate::redis_value!(@object [
+            $($elems,)* $crate::redis_value!($field), $crate::redis_value!($value),
+        ] $($($rest)*)?)
+    };
+
+    (nil) => {
+        $crate::__private::Value::Nil
+    };
+    (ok) => {
+        $crate::__private::Value::Okay
+    };
+    (status($status:expr)) => {
+        $crate::__private::Value::Status(::std::string::String::from($status))
+    };
+    ([$($tt:tt)*]) => {
+        $crate::__private::Value::Bulk($crate::redis_value!(@array [] $($tt)*))
+    };
+    ({$($tt:tt)*}) => {
+        $crate::__private::Value::Bulk($crate::redis_value!(@object [] $($tt)*))
+    };
+    ($other:expr) => {
+        $crate::__private::Scalar::into_value($other)
+    };
+}
+
+/// Values `redis_value!` accepts as scalars
+#[doc(hidden)]
+pub trait Scalar {
+    fn into_value(self) -> Value;
+}
+
+impl Scalar for Value {
+    fn into_value(self) -> Value {
+        self
+    }
+}
+
+impl Scalar for &str {
+    fn into_value(self) -> Value {
+        Value::Data(self.as_bytes().to_vec())
+    }
+}
+
+impl Scalar for String {
+    fn into_value(self) -> Value {
+        Value::Data(self.into_bytes())
+    }
+}
+
+impl Scalar for &[u8] {
+    fn into_value(self) -> Value {
+        Value::Data(self.to_vec())
+    }
+}
+
+impl<const N: usize> Scalar for &[u8; N] {
+    fn into_value(self) -> Value {
+        Value::Data(self.to_vec())
+    }
+}
+
+impl Scalar for Vec<u8> {
+    fn into_value(self) -> Value {
+        Value::Data(self)
+    }
+}
+
+impl<T> Scalar for Option<T>
+where
+    T: Scalar,
+{
+    fn into_value(self) -> Value {
+        self.map_or(Value::Nil, Scalar::into_value)
+    }
+}
+
+macro_rules! int_scalar {
+    ($($ty:ty)*) => {
+        $(
+            impl Scalar for $ty {
+                fn into_value(self) -> Value {
+                    Value::Int(self as i64)
+                }
+            }
+        )*
+    };
+}
+
+int_scalar!(i8 i16 i32 i64 isize u8 u16 u32);
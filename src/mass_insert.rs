@@ -0,0 +1,286 @@
+//! Files for Redis mass insertion
+//!
+//! [`Writer`] turns records into the commands that store them and writes those commands in the
+//! Redis protocol format, ready for `redis-cli --pipe`. Each record is flattened into `field
+//! value` pairs with `encode::to_hash_fields`, so a record written as a hash reads back with
+//! `from_redis_value` into the type it came from. Keys come from a [`KeyTemplate`] such as
+//! `user:{id}`, filled in from the record's own fields.
+//!
+//! ```
+//! # #[macro_use] extern crate serde_derive;
+//! use serde_redis::mass_insert::{KeyTemplate, Writer};
+//!
+//! #[derive(Serialize)]
+//! struct User {
+//!     id: u64,
+//!     name: String,
+//! }
+//!
+//! let key: KeyTemplate = "user:{id}".parse().unwrap();
+//! let mut writer = Writer::new(Vec::new(), key).expire(3600);
+//! writer.write(&User { id: 1, name: "ada".to_owned() }).unwrap();
+//!
+//! // HSET user:1 id 1 name ada, then EXPIRE user:1 3600
+//! assert_eq!(2, writer.commands());
+//! # fn main() {}
+//! ```
+//!
+//! With the `cli` feature the crate also builds `serde-redis-mass-insert`, which does the same for
+//! JSON lines and CSV files.
+use redis::Value;
+use serde::Serialize;
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+use crate::encode;
+use crate::resp::write_command;
+
+#[derive(Debug)]
+pub enum Error {
+    Encode(encode::Error),
+    Io(io::Error),
+    /// The key template is malformed; holds what was wrong
+    Template(String),
+    /// The record lacks a field the key template or store needs, or it is `None`
+    MissingField(String),
+}
+
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Error::Encode(ref err) => Some(err),
+            Error::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Encode(ref err) => write!(f, "could not encode record: {}", err),
+            Error::Io(ref err) => write!(f, "could not write commands: {}", err),
+            Error::Template(ref msg) => write!(f, "invalid key template: {}", msg),
+            Error::MissingField(ref field) => write!(f, "record has no value for `{}`", field),
+        }
+    }
+}
+
+impl From<encode::Error> for Error {
+    fn from(err: encode::Error) -> Error {
+        Error::Encode(err)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+impl From<Error> for redis::RedisError {
+    fn from(err: Error) -> redis::RedisError {
+        redis::RedisError::from((
+            redis::ErrorKind::ClientError,
+            "Record could not be written",
+            err.to_string(),
+        ))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(Vec<u8>),
+    Field(String),
+}
+
+/// A key pattern with `{field}` placeholders, such as `user:{id}` or `{tenant}:orders:{id}`
+///
+/// Placeholders are replaced with the value of the record's field of that name. `{{` and `}}`
+/// stand for literal braces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyTemplate {
+    parts: Vec<Part>,
+}
+
+impl FromStr for KeyTemplate {
+    type Err = Error;
+
+    fn from_str(template: &str) -> Result<KeyTemplate> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let rest = chars.as_str();
+                    let end = rest
+                        .find('}')
+                        .ok_or_else(|| Error::Template(format!("unclosed `{{` in {}", template)))?;
+                    if end == 0 {
+                        return Err(Error::Template(format!("empty `{{}}` in {}", template)));
+                    }
+
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(literal.split_off(0).into_bytes()));
+                    }
+                    parts.push(Part::Field(rest[..end].to_owned()));
+                    chars = rest[end + 1..].chars();
+                }
+                '}' => {
+                    return Err(Error::Template(format!("unmatched `}}` in {}", template)));
+                }
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal.into_bytes()));
+        }
+
+        Ok(KeyTemplate { parts })
+    }
+}
+
+impl KeyTemplate {
+    /// Build the key for a record's `field value` pairs
+    pub fn render(&self, fields: &[(Vec<u8>, Value)]) -> Result<Vec<u8>> {
+        let mut key = Vec::new();
+        for part in &self.parts {
+            match *part {
+                Part::Literal(ref bytes) => key.extend_from_slice(bytes),
+                Part::Field(ref name) => key.extend_from_slice(field(fields, name)?),
+            }
+        }
+
+        Ok(key)
+    }
+}
+
+/// Find a field's value; `None` fields count as missing
+fn field<'a>(fields: &'a [(Vec<u8>, Value)], name: &str) -> Result<&'a [u8]> {
+    fields
+        .iter()
+        .find_map(|(field, value)| match *value {
+            Value::Data(ref bytes) if field == name.as_bytes() => Some(&bytes[..]),
+            _ => None,
+        })
+        .ok_or_else(|| Error::MissingField(name.to_owned()))
+}
+
+/// How each record is stored under its key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Store {
+    /// `HSET` all of the record's fields, except those that are `None`
+    Hash,
+    /// `SET` the key to the named field
+    String(String),
+    /// `RPUSH` the named field onto the list at the key
+    List(String),
+}
+
+/// Writes records as mass insertion commands
+///
+/// Records are stored as hashes unless another [`Store`] is chosen.
+#[derive(Debug)]
+pub struct Writer<W> {
+    writer: W,
+    key: KeyTemplate,
+    store: Store,
+    expire: Option<u64>,
+    commands: usize,
+}
+
+impl<W> Writer<W>
+where
+    W: io::Write,
+{
+    pub fn new(writer: W, key: KeyTemplate) -> Self {
+        Writer {
+            writer,
+            key,
+            store: Store::Hash,
+            expire: None,
+            commands: 0,
+        }
+    }
+
+    pub fn store(mut self, store: Store) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Follow each write with an `EXPIRE` of the key
+    pub fn expire(mut self, seconds: u64) -> Self {
+        self.expire = Some(seconds);
+        self
+    }
+
+    /// Write the commands that store `record`
+    pub fn write<T>(&mut self, record: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        let fields = encode::to_hash_fields(record)?;
+        let key = self.key.render(&fields)?;
+
+        match self.store {
+            Store::Hash => {
+                let mut args: Vec<&[u8]> = vec![b"HSET", &key];
+                for (field, value) in &fields {
+                    if let Value::Data(ref bytes) = *value {
+                        args.push(field);
+                        args.push(bytes);
+                    }
+                }
+                if args.len() == 2 {
+                    return Err(
+                        encode::Error::expected_hash("HSET needs at least one field").into(),
+                    );
+                }
+
+                write_command(&mut self.writer, &args)?;
+            }
+            Store::String(ref name) => {
+                write_command(&mut self.writer, &[b"SET", &key, field(&fields, name)?])?;
+            }
+            Store::List(ref name) => {
+                write_command(&mut self.writer, &[b"RPUSH", &key, field(&fields, name)?])?;
+            }
+        }
+        self.commands += 1;
+
+        if let Some(seconds) = self.expire {
+            let seconds = seconds.to_string();
+            write_command(&mut self.writer, &[b"EXPIRE", &key, seconds.as_bytes()])?;
+            self.commands += 1;
+        }
+
+        Ok(())
+    }
+
+    /// How many commands have been written
+    pub fn commands(&self) -> usize {
+        self.commands
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        Ok(self.writer.flush()?)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
@@ -0,0 +1,357 @@
+use serde::de::{self, Deserialize, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::{slice, vec};
+
+use crate::resp;
+
+/// An ordered list of key/value pairs decoded from a flat `Bulk`
+///
+/// Replies such as `HGETALL` and `ZRANGE ... WITHSCORES` alternate keys and values in a single
+/// `Bulk`. Maps already decode from that shape, but lose the reply order (or require a `BTreeMap`
+/// ordering). `Pairs` keeps every pair in the order Redis returned them.
+///
+/// Keys and values decode like any other value, so scores accept `inf` and `-inf`. Keys and
+/// values may also be structs or maps: either a nested `Bulk` of field/value pairs, such as a
+/// script may return, or a single `Data` holding the struct packed with `resp::to_vec`, as
+/// `pubsub::RedisCodec` packs payloads. Sorted set members written that way decode straight into
+/// the struct.
+///
+/// ```no_run
+/// # use serde_redis::{from_redis_value, Pairs};
+/// # fn f(reply: redis::Value) -> serde_redis::decode::Result<()> {
+/// // ZRANGE leaderboard 0 -1 WITHSCORES
+/// let scores: Pairs<String, f64> = from_redis_value(reply)?;
+/// for (member, score) in &scores {
+///     println!("{} {}", member, score);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Pairs<K, V>(pub Vec<(K, V)>);
+
+impl<K, V> Pairs<K, V> {
+    /// Unwrap into the underlying list of pairs
+    pub fn into_inner(self) -> Vec<(K, V)> {
+        self.0
+    }
+}
+
+impl<K, V> Default for Pairs<K, V> {
+    fn default() -> Self {
+        Pairs(Vec::new())
+    }
+}
+
+impl<K, V> Deref for Pairs<K, V> {
+    type Target = [(K, V)];
+
+    fn deref(&self) -> &[(K, V)] {
+        &self.0
+    }
+}
+
+impl<K, V> From<Pairs<K, V>> for Vec<(K, V)> {
+    fn from(pairs: Pairs<K, V>) -> Self {
+        pairs.0
+    }
+}
+
+impl<K, V> IntoIterator for Pairs<K, V> {
+    type Item = (K, V);
+    type IntoIter = vec::IntoIter<(K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a Pairs<K, V> {
+    type Item = &'a (K, V);
+    type IntoIter = slice::Iter<'a, (K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<'de, K, V> Deserialize<'de> for Pairs<K, V>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserialize(deserializer).map(Pairs)
+    }
+}
+
+/// Deserialize a flat pair list into `Vec<(K, V)>`
+///
+/// `Vec<(K, V)>` on its own expects each element to be a nested `Bulk`. Use this with
+/// `#[serde(deserialize_with = "serde_redis::pairs::deserialize")]` on fields that hold a flat
+/// pair reply instead.
+pub fn deserialize<'de, D, K, V>(deserializer: D) -> Result<Vec<(K, V)>, D::Error>
+where
+    D: de::Deserializer<'de>,
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    deserializer.deserialize_map(PairsVisitor(PhantomData))
+}
+
+struct PairsVisitor<K, V>(PhantomData<(K, V)>);
+
+impl<'de, K, V> Visitor<'de> for PairsVisitor<K, V>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    type Value = Vec<(K, V)>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a flat list of alternating keys and values")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut pairs = Vec::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some(key) = map.next_key_seed(MemberSeed(PhantomData))? {
+            let value = map.next_value_seed(MemberSeed(PhantomData))?;
+            pairs.push((key, value));
+        }
+
+        Ok(pairs)
+    }
+}
+
+/// Decodes `T` through `Member`
+struct MemberSeed<T>(PhantomData<T>);
+
+impl<'de, T> DeserializeSeed<'de> for MemberSeed<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = T;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<T, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        T::deserialize(Member(deserializer))
+    }
+}
+
+/// A key or value of a pair list; structs and maps are also read from packed `Data`
+struct Member<D>(D);
+
+macro_rules! forward {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            #[inline]
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, D::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.0.$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+impl<'de, D> de::Deserializer<'de> for Member<D>
+where
+    D: de::Deserializer<'de>,
+{
+    type Error = D::Error;
+
+    forward! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_i128();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_u128();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_identifier();
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_ignored_any();
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.0.deserialize_any(Packed {
+            visitor,
+            target: Target::Map,
+        })
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.0.deserialize_any(Packed {
+            visitor,
+            target: Target::Struct(name, fields),
+        })
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.0.is_human_readable()
+    }
+}
+
+enum Target {
+    Map,
+    Struct(&'static str, &'static [&'static str]),
+}
+
+/// Reads a struct or map from whichever form the reply holds it in
+struct Packed<V> {
+    visitor: V,
+    target: Target,
+}
+
+impl<V> Packed<V> {
+    /// Decode the struct from the RESP reply it was packed into
+    fn unpack<'de, R, E>(self, mut de: resp::Deserializer<R>) -> Result<V::Value, E>
+    where
+        V: Visitor<'de>,
+        R: resp::Read<'de>,
+        E: de::Error,
+    {
+        let value = match self.target {
+            Target::Map => de::Deserializer::deserialize_map(&mut de, self.visitor),
+            Target::Struct(name, fields) => {
+                de::Deserializer::deserialize_struct(&mut de, name, fields, self.visitor)
+            }
+        };
+
+        value
+            .and_then(|value| de.end().map(|()| value))
+            .map_err(E::custom)
+    }
+}
+
+impl<'de, V> Visitor<'de> for Packed<V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.visitor.expecting(f)
+    }
+
+    fn visit_unit<E>(self) -> Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visitor.visit_unit()
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visit_borrowed_bytes(v.as_bytes())
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visit_bytes(v.as_bytes())
+    }
+
+    fn visit_borrowed_bytes<E>(self, v: &'de [u8]) -> Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.unpack(resp::Deserializer::from_slice(v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.unpack(resp::Deserializer::from_reader(v))
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<V::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        self.visitor.visit_map(FlatMap(seq))
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<V::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        self.visitor.visit_map(map)
+    }
+}
+
+/// A sequence of alternating keys and values, read as a map
+struct FlatMap<A>(A);
+
+impl<'de, A> MapAccess<'de> for FlatMap<A>
+where
+    A: SeqAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, A::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        self.0.next_element_seed(seed)
+    }
+
+    fn next_value_seed<T>(&mut self, seed: T) -> Result<T::Value, A::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.0.next_element_seed(seed)? {
+            Some(value) => Ok(value),
+            None => Err(de::Error::custom("key without a value")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.0.size_hint().map(|len| len / 2)
+    }
+}
@@ -0,0 +1,244 @@
+//! Typed decoding of pipeline replies
+//!
+//! A pipeline reply is a `Bulk` with one value per (non-ignored) command. [`from_replies`] decodes
+//! it into a tuple, a struct (fields are matched by position), or a `Vec`, and reports which slot
+//! failed when one of them does not decode. Replies left over after `T` is decoded are an error,
+//! as they are for `Deserializer::end`.
+//!
+//! ```no_run
+//! # #[macro_use] extern crate serde_derive;
+//! # fn f(con: &mut redis::Connection) -> redis::RedisResult<()> {
+//! use serde_redis::pipeline::{self, Slot};
+//!
+//! #[derive(Deserialize)]
+//! struct User {
+//!     name: String,
+//! }
+//!
+//! let mut pipe = redis::pipe();
+//! pipe.hgetall("user:1").get("visits:1").lrange("tags:1", 0, -1);
+//!
+//! let (user, visits, tags): (User, Option<u64>, Slot<Vec<String>>) =
+//!     pipeline::query(&pipe, con)?;
+//! # Ok(())
+//! # }
+//! # fn main() {}
+//! ```
+use redis::{Arg, ConnectionLike, Pipeline, RedisResult, Value};
+use serde::de::{self, Deserialize, DeserializeSeed, Visitor};
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::{error, fmt};
+
+use crate::cow_iter::CowIter;
+use crate::decode::{self, Deserializer};
+use crate::IntoCow;
+
+/// Error decoding one reply slot of a pipeline
+#[derive(Debug)]
+pub struct SlotError {
+    /// Position of the reply in the pipeline result
+    pub index: usize,
+    /// The name of the command that produced the reply, when it can be identified
+    ///
+    /// Only the name is kept, since arguments may hold large or sensitive values. Replies are
+    /// matched to commands by position, so the command is unknown when the reply does not have
+    /// one slot per command, as when some commands are marked with `ignore()`.
+    pub command: Option<String>,
+    pub error: decode::Error,
+}
+
+impl error::Error for SlotError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl fmt::Display for SlotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.command {
+            Some(ref command) => write!(f, "slot {} ({}): {}", self.index, command, self.error),
+            None => write!(f, "slot {}: {}", self.index, self.error),
+        }
+    }
+}
+
+impl From<SlotError> for redis::RedisError {
+    fn from(err: SlotError) -> redis::RedisError {
+        redis::RedisError::from((
+            redis::ErrorKind::TypeError,
+            "Pipeline response could not be deserialized",
+            err.to_string(),
+        ))
+    }
+}
+
+/// The outcome of decoding a single slot
+///
+/// Use `Slot<T>` in place of `T` for replies that are allowed to fail without failing the rest of
+/// the pipeline. Decoded by `decode::Deserializer`, at any depth, the slot holds the error
+/// decoding failed with; other deserializers' errors are kept as `decode::Error::Custom`, unless
+/// their source is a `decode::Error`.
+#[derive(Debug)]
+pub struct Slot<T>(pub decode::Result<T>);
+
+impl<T> Slot<T> {
+    pub fn into_result(self) -> decode::Result<T> {
+        self.0
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Slot<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(decode::SLOT, SlotVisitor(PhantomData))
+    }
+}
+
+struct SlotVisitor<T>(PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for SlotVisitor<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = Slot<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a pipeline reply")
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Slot<T>, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        // `decode::Deserializer` fails with an error whose source is the original
+        let result = T::deserialize(deserializer).map_err(|err| {
+            error::Error::source(&err)
+                .and_then(|source| source.downcast_ref::<decode::Error>())
+                .cloned()
+                .unwrap_or_else(|| decode::Error::Custom(err.to_string()))
+        });
+        Ok(Slot(result))
+    }
+}
+
+/// Run `pipe` and decode the replies into `T`
+pub fn query<T, C>(pipe: &Pipeline, con: &mut C) -> RedisResult<T>
+where
+    T: de::DeserializeOwned,
+    C: ConnectionLike,
+{
+    let value: Value = pipe.query(con)?;
+    Ok(from_replies(pipe, value)?)
+}
+
+/// Decode the replies of `pipe` into `T`, one slot per element
+pub fn from_replies<'a, 'de, T, RV>(pipe: &Pipeline, rv: RV) -> Result<T, SlotError>
+where
+    T: Deserialize<'de>,
+    RV: IntoCow<'a>,
+{
+    let values = match rv.into_cow() {
+        Cow::Owned(Value::Bulk(values)) => Cow::Owned(values),
+        Cow::Borrowed(Value::Bulk(values)) => Cow::Borrowed(values),
+        v => {
+            return Err(SlotError {
+                index: 0,
+                command: None,
+                error: decode::Error::wrong_value(format!("expected bulk but got {:?}", v)),
+            });
+        }
+    };
+
+    let count = values.len();
+    let slot = Cell::new(0);
+    let slot_error = |index, error| SlotError {
+        index,
+        command: command_at(pipe, count, index),
+        error,
+    };
+
+    let mut seq = SlotSeq {
+        iter: CowIter::new(values),
+        index: 0,
+        slot: &slot,
+    };
+    let value = T::deserialize(Replies { seq: &mut seq }).map_err(|error| {
+        let index = slot.get();
+        slot_error(index, error)
+    })?;
+
+    let index = seq.index;
+    match seq.iter.count() {
+        0 => Ok(value),
+        left => Err(slot_error(index, decode::Error::TrailingValues(left))),
+    }
+}
+
+fn command_at(pipe: &Pipeline, count: usize, index: usize) -> Option<String> {
+    if pipe.cmd_iter().count() != count {
+        return None;
+    }
+
+    let name = match pipe.cmd_iter().nth(index)?.args_iter().next()? {
+        Arg::Simple(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+        Arg::Cursor => return None,
+    };
+    Some(name)
+}
+
+/// Deserializes a pipeline reply as a sequence, recording the slot being decoded
+struct Replies<'r, 'a, 's> {
+    seq: &'r mut SlotSeq<'a, 's>,
+}
+
+impl<'r, 'a, 's, 'de> de::Deserializer<'de> for Replies<'r, 'a, 's> {
+    type Error = decode::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> decode::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(self.seq)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct SlotSeq<'a, 's> {
+    iter: CowIter<'a>,
+    index: usize,
+    slot: &'s Cell<usize>,
+}
+
+impl<'a, 's, 'de> de::SeqAccess<'de> for SlotSeq<'a, 's> {
+    type Error = decode::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> decode::Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.slot.set(self.index);
+        match self.iter.next() {
+            Some(v) => {
+                self.index += 1;
+                seed.deserialize(Deserializer::new(v)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.iter.size_hint().1
+    }
+}
@@ -0,0 +1,221 @@
+//! Typed Pub/Sub payloads
+//!
+//! A message payload is a single string, so values are framed by a [`Codec`] before publishing
+//! and unframed again on the subscriber side. [`RedisCodec`] is the default: it writes values in
+//! the Redis protocol format with `resp::to_vec`, so a payload decodes into `T` exactly as a reply
+//! of the same shape would. With the `json` or `msgpack` features, `JsonCodec` and
+//! `MsgpackCodec` are available for payloads shared with other languages.
+//!
+//! ```no_run
+//! # #[macro_use] extern crate serde_derive;
+//! # fn f(con: &mut redis::Connection, sub: &mut redis::Connection) -> redis::RedisResult<()> {
+//! use serde_redis::pubsub;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Job {
+//!     id: u64,
+//! }
+//!
+//! pubsub::publish(con, "jobs", &Job { id: 1 })?;
+//!
+//! let mut sub = sub.as_pubsub();
+//! sub.subscribe("jobs")?;
+//! let job: Job = pubsub::from_msg(&sub.get_message()?)?;
+//! # Ok(())
+//! # }
+//! # fn main() {}
+//! ```
+use redis::{ConnectionLike, Msg, RedisResult, ToRedisArgs};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::{error, fmt};
+
+use crate::{decode, encode, resp};
+
+#[derive(Debug)]
+pub enum Error {
+    Encode(encode::Error),
+    Decode(decode::Error),
+    /// The payload is not valid for the codec, or the codec's own format failed
+    Codec(Box<dyn error::Error + Send + Sync>),
+}
+
+impl Error {
+    pub fn codec<E>(err: E) -> Error
+    where
+        E: Into<Box<dyn error::Error + Send + Sync>>,
+    {
+        Error::Codec(err.into())
+    }
+}
+
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Encode(ref err) => Some(err),
+            Error::Decode(ref err) => Some(err),
+            Error::Codec(ref err) => Some(&**err),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Encode(ref err) => write!(f, "could not encode payload: {}", err),
+            Error::Decode(ref err) => write!(f, "could not decode payload: {}", err),
+            Error::Codec(ref err) => write!(f, "codec error: {}", err),
+        }
+    }
+}
+
+impl From<encode::Error> for Error {
+    fn from(err: encode::Error) -> Error {
+        Error::Encode(err)
+    }
+}
+
+impl From<decode::Error> for Error {
+    fn from(err: decode::Error) -> Error {
+        Error::Decode(err)
+    }
+}
+
+impl From<resp::Error> for Error {
+    fn from(err: resp::Error) -> Error {
+        match err {
+            resp::Error::Encode(err) => Error::Encode(err),
+            resp::Error::Decode(err) => Error::Decode(err),
+            err => Error::codec(err),
+        }
+    }
+}
+
+impl From<Error> for redis::RedisError {
+    fn from(err: Error) -> redis::RedisError {
+        let kind = match err {
+            Error::Decode(_) => redis::ErrorKind::TypeError,
+            _ => redis::ErrorKind::ClientError,
+        };
+        redis::RedisError::from((kind, "Payload could not be converted", err.to_string()))
+    }
+}
+
+/// Turns values into message payloads and back
+pub trait Codec {
+    fn encode<T>(&self, value: &T) -> Result<Vec<u8>>
+    where
+        T: Serialize + ?Sized;
+
+    fn decode<T>(&self, payload: &[u8]) -> Result<T>
+    where
+        T: DeserializeOwned;
+}
+
+/// Payloads in the Redis protocol format, using the crate's `Serializer` and `Deserializer`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RedisCodec;
+
+impl Codec for RedisCodec {
+    fn encode<T>(&self, value: &T) -> Result<Vec<u8>>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(resp::to_vec(value)?)
+    }
+
+    fn decode<T>(&self, payload: &[u8]) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let value = redis::parse_redis_value(payload).map_err(Error::codec)?;
+        Ok(crate::from_redis_value(value)?)
+    }
+}
+
+/// JSON payloads, through `serde_json`
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonCodec;
+
+#[cfg(feature = "json")]
+impl Codec for JsonCodec {
+    fn encode<T>(&self, value: &T) -> Result<Vec<u8>>
+    where
+        T: Serialize + ?Sized,
+    {
+        serde_json::to_vec(value).map_err(Error::codec)
+    }
+
+    fn decode<T>(&self, payload: &[u8]) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        serde_json::from_slice(payload).map_err(Error::codec)
+    }
+}
+
+/// MessagePack payloads, through `rmp-serde`; structs are written as maps
+#[cfg(feature = "msgpack")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MsgpackCodec;
+
+#[cfg(feature = "msgpack")]
+impl Codec for MsgpackCodec {
+    fn encode<T>(&self, value: &T) -> Result<Vec<u8>>
+    where
+        T: Serialize + ?Sized,
+    {
+        rmp_serde::to_vec_named(value).map_err(Error::codec)
+    }
+
+    fn decode<T>(&self, payload: &[u8]) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        rmp_serde::from_slice(payload).map_err(Error::codec)
+    }
+}
+
+/// Decode the payload of `msg` with `RedisCodec`
+pub fn from_msg<T>(msg: &Msg) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    from_msg_with(&RedisCodec, msg)
+}
+
+/// Decode the payload of `msg` with `codec`
+pub fn from_msg_with<C, T>(codec: &C, msg: &Msg) -> Result<T>
+where
+    C: Codec,
+    T: DeserializeOwned,
+{
+    codec.decode(msg.get_payload_bytes())
+}
+
+/// `PUBLISH channel payload` with `value` encoded by `RedisCodec`
+///
+/// Returns the number of subscribers that received the message.
+pub fn publish<C, K, T>(con: &mut C, channel: K, value: &T) -> RedisResult<usize>
+where
+    C: ConnectionLike,
+    K: ToRedisArgs,
+    T: Serialize + ?Sized,
+{
+    publish_with(con, &RedisCodec, channel, value)
+}
+
+/// `PUBLISH channel payload` with `value` encoded by `codec`
+pub fn publish_with<C, D, K, T>(con: &mut C, codec: &D, channel: K, value: &T) -> RedisResult<usize>
+where
+    C: ConnectionLike,
+    D: Codec,
+    K: ToRedisArgs,
+    T: Serialize + ?Sized,
+{
+    let payload = codec.encode(value)?;
+    redis::cmd("PUBLISH").arg(channel).arg(payload).query(con)
+}
@@ -0,0 +1,779 @@
+use redis::Value;
+use serde::de;
+use std::{io, str};
+
+use super::read::{IoRead, Read, Reference, SliceRead};
+use super::{Error, Result};
+
+/// How deeply aggregates may nest before the input is rejected, so hostile input cannot overflow
+/// the stack
+const MAX_DEPTH: usize = 128;
+
+/// Deserialize an instance of `T` from a single RESP reply
+///
+/// Errors if bytes are left over after the reply.
+pub fn from_slice<'de, T>(input: &'de [u8]) -> Result<T>
+where
+    T: de::Deserialize<'de>,
+{
+    let mut de = Deserializer::from_slice(input);
+    let value = T::deserialize(&mut de)?;
+    de.end()?;
+    Ok(value)
+}
+
+/// Deserialize an instance of `T` from a single RESP reply read from `reader`
+///
+/// The reply is read as it is deserialized, so only the string being decoded is held in memory
+/// and a large bulk or map reply can be processed element by element. Errors if anything follows
+/// the reply; to read one reply from a connection that stays open, use
+/// `Deserializer::from_reader` instead.
+pub fn from_reader<R, T>(reader: R) -> Result<T>
+where
+    R: io::BufRead,
+    T: de::DeserializeOwned,
+{
+    let mut de = Deserializer::from_reader(reader);
+    let value = T::deserialize(&mut de)?;
+    de.end()?;
+    Ok(value)
+}
+
+/// One reply header; strings hold their payload, aggregates hold their length
+#[derive(Debug)]
+pub(super) enum Reply<'de, 's> {
+    Nil,
+    Status(Reference<'de, 's, [u8]>),
+    Int(i64),
+    Data(Reference<'de, 's, [u8]>),
+    Bulk(usize),
+    // RESP3
+    Map(usize),
+    Set(usize),
+    Push(usize),
+    Double(f64),
+    Boolean(bool),
+    BigNumber(Reference<'de, 's, str>),
+    Verbatim(Reference<'de, 's, str>, Reference<'de, 's, [u8]>),
+}
+
+/// The type of the next reply, told from its first bytes without reading it
+#[derive(Debug, PartialEq)]
+enum Kind {
+    Nil,
+    Status,
+    Int,
+    Bulk,
+    Map,
+    Set,
+    Boolean,
+    Double,
+    Text,
+    Other,
+}
+
+/// Deserializes RESP replies from a byte slice or an `io::BufRead`
+///
+/// Each `deserialize` call through `&mut Deserializer` reads one reply, so several replies in a
+/// row (as written in response to a pipeline) can be read one after the other.
+#[derive(Debug)]
+pub struct Deserializer<R> {
+    read: R,
+    scratch: Vec<u8>,
+    depth: usize,
+}
+
+impl<'de> Deserializer<SliceRead<'de>> {
+    pub fn from_slice(input: &'de [u8]) -> Self {
+        Deserializer::new(SliceRead::new(input))
+    }
+}
+
+impl<R> Deserializer<IoRead<R>>
+where
+    R: io::BufRead,
+{
+    pub fn from_reader(reader: R) -> Self {
+        Deserializer::new(IoRead::new(reader))
+    }
+}
+
+impl<'de, R> Deserializer<R>
+where
+    R: Read<'de>,
+{
+    pub fn new(read: R) -> Self {
+        Deserializer {
+            read,
+            scratch: Vec::new(),
+            depth: 0,
+        }
+    }
+
+    /// Check that the whole input was read
+    pub fn end(&mut self) -> Result<()> {
+        if self.read.peek(1)?.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::TrailingBytes(self.read.position()))
+        }
+    }
+
+    fn read_int(&mut self) -> Result<i64> {
+        let start = self.read.position();
+        let line = self.read.read_line(&mut self.scratch)?;
+        match str::from_utf8(&line).ok().and_then(|s| s.parse().ok()) {
+            Some(i) => Ok(i),
+            None => syntax(start, "invalid integer"),
+        }
+    }
+
+    fn read_len(&mut self) -> Result<Option<usize>> {
+        let start = self.read.position();
+        match self.read_int()? {
+            -1 => Ok(None),
+            len if len < 0 => syntax(start, "invalid length"),
+            len => Ok(Some(len as usize)),
+        }
+    }
+
+    fn read_aggregate_len(&mut self) -> Result<usize> {
+        let start = self.read.position();
+        match self.read_len()? {
+            Some(len) => Ok(len),
+            None => syntax(start, "invalid length"),
+        }
+    }
+
+    /// Run `f` one aggregate deeper, failing once `MAX_DEPTH` is reached
+    pub(super) fn nested<T, F>(&mut self, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut Self) -> Result<T>,
+    {
+        if self.depth == MAX_DEPTH {
+            return syntax(self.read.position(), "recursion limit exceeded");
+        }
+
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Read past an attribute, which describes the reply that follows it
+    fn skip_attribute(&mut self) -> Result<()> {
+        let len = self.read_aggregate_len()?;
+        self.nested(|de| {
+            for _ in 0..len * 2 {
+                de.skip_reply()?;
+            }
+
+            Ok(())
+        })
+    }
+
+    pub(super) fn next_reply(&mut self) -> Result<Reply<'de, '_>> {
+        let start = self.read.position();
+        let tag = *self.read.peek(1)?.first().ok_or(Error::Eof)?;
+        self.read.consume(1);
+
+        match tag {
+            b'+' => Ok(Reply::Status(self.read.read_line(&mut self.scratch)?)),
+            b'-' => {
+                let msg = self.read.read_line(&mut self.scratch)?;
+                Err(Error::Server(String::from_utf8_lossy(&msg).into_owned()))
+            }
+            b':' => Ok(Reply::Int(self.read_int()?)),
+            b'$' => match self.read_len()? {
+                Some(len) => Ok(Reply::Data(self.read.read_blob(len, &mut self.scratch)?)),
+                None => Ok(Reply::Nil),
+            },
+            b'*' => Ok(match self.read_len()? {
+                Some(len) => Reply::Bulk(len),
+                None => Reply::Nil,
+            }),
+            b'_' => match &*self.read.read_line(&mut self.scratch)? {
+                b"" => Ok(Reply::Nil),
+                _ => syntax(start, "invalid null"),
+            },
+            b'#' => match &*self.read.read_line(&mut self.scratch)? {
+                b"t" => Ok(Reply::Boolean(true)),
+                b"f" => Ok(Reply::Boolean(false)),
+                _ => syntax(start, "invalid boolean"),
+            },
+            b',' => {
+                let line = self.read.read_line(&mut self.scratch)?;
+                match str::from_utf8(&line).ok().and_then(|s| s.parse().ok()) {
+                    Some(d) => Ok(Reply::Double(d)),
+                    None => syntax(start, "invalid double"),
+                }
+            }
+            b'(' => {
+                let line = self.read.read_line(&mut self.scratch)?;
+                let digits = line.strip_prefix(b"-").unwrap_or(&line);
+                if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+                    return syntax(start, "invalid big number");
+                }
+                Ok(Reply::BigNumber(line.into_str()?))
+            }
+            b'=' => {
+                let len = self.read_aggregate_len()?;
+                let blob = self.read.read_blob(len, &mut self.scratch)?;
+                if blob.len() < 4 || blob[3] != b':' {
+                    return syntax(start, "invalid verbatim string");
+                }
+                Ok(Reply::Verbatim(
+                    blob.slice(0, 3).into_str()?,
+                    blob.slice(4, blob.len()),
+                ))
+            }
+            b'!' => {
+                let len = self.read_aggregate_len()?;
+                let msg = self.read.read_blob(len, &mut self.scratch)?;
+                Err(Error::Server(String::from_utf8_lossy(&msg).into_owned()))
+            }
+            b'%' => Ok(Reply::Map(self.read_aggregate_len()?)),
+            b'~' => Ok(Reply::Set(self.read_aggregate_len()?)),
+            b'>' => Ok(Reply::Push(self.read_aggregate_len()?)),
+            b'|' => {
+                self.skip_attribute()?;
+                self.next_reply()
+            }
+            _ => syntax(start, "unknown reply type"),
+        }
+    }
+
+    /// Tell the type of the next reply from its first two bytes, skipping attributes before it;
+    /// `None` at the end of the input
+    fn peek_kind(&mut self) -> Result<Option<Kind>> {
+        loop {
+            let head = self.read.peek(2)?;
+            let kind = match (head.first(), head.get(1)) {
+                (None, _) => return Ok(None),
+                (Some(b'|'), _) => {
+                    self.read.consume(1);
+                    self.skip_attribute()?;
+                    continue;
+                }
+                (Some(b'$'), Some(b'-')) | (Some(b'*'), Some(b'-')) | (Some(b'_'), _) => Kind::Nil,
+                (Some(b'+'), _) => Kind::Status,
+                (Some(b':'), _) => Kind::Int,
+                (Some(b'*'), _) => Kind::Bulk,
+                (Some(b'%'), _) => Kind::Map,
+                (Some(b'~'), _) | (Some(b'>'), _) => Kind::Set,
+                (Some(b'#'), _) => Kind::Boolean,
+                (Some(b','), _) => Kind::Double,
+                (Some(b'('), _) | (Some(b'='), _) => Kind::Text,
+                _ => Kind::Other,
+            };
+
+            return Ok(Some(kind));
+        }
+    }
+
+    /// Read past the next reply, including all of its elements
+    fn skip_reply(&mut self) -> Result<()> {
+        let len = match self.next_reply()? {
+            Reply::Bulk(len) | Reply::Set(len) | Reply::Push(len) => len,
+            Reply::Map(len) => len * 2,
+            _ => return Ok(()),
+        };
+
+        self.nested(|de| {
+            for _ in 0..len {
+                de.skip_reply()?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn read_bytes(&mut self) -> Result<Reference<'de, '_, [u8]>> {
+        match self.next_reply()? {
+            Reply::Data(bytes) | Reply::Verbatim(_, bytes) => Ok(bytes),
+            reply => Err(wrong_value("Expected bytes, but got", &reply)),
+        }
+    }
+
+    fn read_str(&mut self) -> Result<Reference<'de, '_, str>> {
+        match self.next_reply()? {
+            Reply::Data(bytes) | Reply::Status(bytes) | Reply::Verbatim(_, bytes) => {
+                bytes.into_str()
+            }
+            Reply::BigNumber(s) => Ok(s),
+            reply => Err(wrong_value("Expected Data or Status, got", &reply)),
+        }
+    }
+
+    /// Read the header of a sequence and return its number of elements
+    fn read_bulk(&mut self) -> Result<usize> {
+        match self.next_reply()? {
+            Reply::Bulk(len) | Reply::Set(len) | Reply::Push(len) => Ok(len),
+            reply => Err(wrong_value("expected bulk but got", &reply)),
+        }
+    }
+
+    /// Read the header of a map (or a bulk of alternating keys and values) and return its number
+    /// of elements, counting keys and values separately
+    fn read_map(&mut self) -> Result<usize> {
+        match self.next_reply()? {
+            Reply::Bulk(len) => Ok(len),
+            Reply::Map(len) => Ok(len * 2),
+            reply => Err(wrong_value("expected map but got", &reply)),
+        }
+    }
+}
+
+fn syntax<T>(offset: usize, msg: &str) -> Result<T> {
+    Err(Error::Syntax(offset, msg.to_owned()))
+}
+
+fn wrong_value(msg: &str, reply: &Reply) -> Error {
+    Error::Decode(crate::decode::Error::wrong_value(format!(
+        "{} {:?}",
+        msg, reply
+    )))
+}
+
+macro_rules! impl_num {
+    ($ty:ty, $deserialize_method:ident, $visitor_method:ident) => {
+        #[inline]
+        fn $deserialize_method<V>(self, visitor: V) -> Result<V::Value>
+        where
+            V: de::Visitor<'de>,
+        {
+            let value = match self.next_reply()? {
+                Reply::Data(bytes) => str::from_utf8(&bytes)?.parse::<$ty>()?,
+                Reply::BigNumber(s) => s.parse::<$ty>()?,
+                Reply::Int(i) => {
+                    crate::decode::int_in_range(i, stringify!($ty)).map_err(Error::Decode)?
+                }
+                reply => return Err(wrong_value("Expected Data or Int, got", &reply)),
+            };
+
+            visitor.$visitor_method(value)
+        }
+    };
+}
+
+macro_rules! impl_float {
+    ($ty:ty, $deserialize_method:ident, $visitor_method:ident) => {
+        #[inline]
+        fn $deserialize_method<V>(self, visitor: V) -> Result<V::Value>
+        where
+            V: de::Visitor<'de>,
+        {
+            let value = match self.next_reply()? {
+                Reply::Data(bytes) => str::from_utf8(&bytes)?.parse::<$ty>()?,
+                Reply::BigNumber(s) => s.parse::<$ty>()?,
+                Reply::Int(i) => i as $ty,
+                Reply::Double(d) => d as $ty,
+                reply => return Err(wrong_value("Expected Data, Int or Double, got", &reply)),
+            };
+
+            visitor.$visitor_method(value)
+        }
+    };
+}
+
+impl<'de, R> de::Deserializer<'de> for &mut Deserializer<R>
+where
+    R: Read<'de>,
+{
+    type Error = Error;
+
+    /// Replies describe themselves as in `decode::Deserializer`: nil is a unit, data is a string
+    /// when it is UTF-8 and bytes otherwise, and arrays are sequences. RESP3 maps, booleans and
+    /// doubles are read as such.
+    #[inline]
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.peek_kind()? {
+            Some(Kind::Nil) => {
+                self.next_reply()?;
+                visitor.visit_unit()
+            }
+            Some(Kind::Int) => self.deserialize_i64(visitor),
+            Some(Kind::Bulk) | Some(Kind::Set) => self.deserialize_seq(visitor),
+            Some(Kind::Map) => self.deserialize_map(visitor),
+            Some(Kind::Boolean) => self.deserialize_bool(visitor),
+            Some(Kind::Double) => self.deserialize_f64(visitor),
+            Some(Kind::Status) | Some(Kind::Text) => self.deserialize_str(visitor),
+            Some(Kind::Other) => {
+                let bytes = self.read_bytes()?;
+                match bytes.into_str() {
+                    Ok(Reference::Borrowed(s)) => visitor.visit_borrowed_str(s),
+                    Ok(Reference::Copied(s)) => visitor.visit_str(s),
+                    Err(_) => match bytes {
+                        Reference::Borrowed(bytes) => visitor.visit_borrowed_bytes(bytes),
+                        Reference::Copied(bytes) => visitor.visit_bytes(bytes),
+                    },
+                }
+            }
+            None => Err(Error::Eof),
+        }
+    }
+
+    #[inline]
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.read_str()? {
+            Reference::Borrowed(s) => visitor.visit_borrowed_str(s),
+            Reference::Copied(s) => visitor.visit_str(s),
+        }
+    }
+
+    #[inline]
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    #[inline]
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    /// Nil is a unit, as `Serializer` writes it; anything else is read as a string
+    #[inline]
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if self.peek_kind()? == Some(Kind::Nil) {
+            self.next_reply()?;
+            return visitor.visit_unit();
+        }
+
+        self.deserialize_str(visitor)
+    }
+
+    #[inline]
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    impl_num!(u8, deserialize_u8, visit_u8);
+    impl_num!(u16, deserialize_u16, visit_u16);
+    impl_num!(u32, deserialize_u32, visit_u32);
+    impl_num!(u64, deserialize_u64, visit_u64);
+
+    impl_num!(i8, deserialize_i8, visit_i8);
+    impl_num!(i16, deserialize_i16, visit_i16);
+    impl_num!(i32, deserialize_i32, visit_i32);
+    impl_num!(i64, deserialize_i64, visit_i64);
+
+    impl_num!(u128, deserialize_u128, visit_u128);
+    impl_num!(i128, deserialize_i128, visit_i128);
+
+    impl_float!(f32, deserialize_f32, visit_f32);
+    impl_float!(f64, deserialize_f64, visit_f64);
+
+    #[inline]
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if self.peek_kind()? == Some(Kind::Boolean) {
+            if let Reply::Boolean(b) = self.next_reply()? {
+                return visitor.visit_bool(b);
+            }
+        }
+
+        let b = match &*self.read_str()? {
+            "1" | "true" | "True" => true,
+            "0" | "false" | "False" => false,
+            s => {
+                return Err(Error::Decode(crate::decode::Error::wrong_value(format!(
+                    "Expected 1/0/true/false/True/False, got {}",
+                    s
+                ))))
+            }
+        };
+
+        visitor.visit_bool(b)
+    }
+
+    #[inline]
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.read_bytes()? {
+            Reference::Borrowed(bytes) => visitor.visit_borrowed_bytes(bytes),
+            Reference::Copied(bytes) => visitor.visit_bytes(bytes),
+        }
+    }
+
+    #[inline]
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    #[inline]
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.peek_kind()? {
+            Some(Kind::Nil) => {
+                self.next_reply()?;
+                visitor.visit_none()
+            }
+            Some(_) => visitor.visit_some(self),
+            None => visitor.visit_none(),
+        }
+    }
+
+    #[inline]
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let len = self.read_bulk()?;
+        self.nested(|de| {
+            let mut access = Elements { de, remaining: len };
+            let value = visitor.visit_seq(&mut access)?;
+            access.skip_rest()?;
+            Ok(value)
+        })
+    }
+
+    #[inline]
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    #[inline]
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    #[inline]
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let len = self.read_map()?;
+        self.nested(|de| {
+            let mut access = Elements { de, remaining: len };
+            let value = visitor.visit_map(&mut access)?;
+            access.skip_rest()?;
+            Ok(value)
+        })
+    }
+
+    #[inline]
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
+
+    #[inline]
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_unit(visitor)
+    }
+
+    #[inline]
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    #[inline]
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_enum(Variant { de: self })
+    }
+
+    /// Unlike `deserialize_any`, this reads past replies of any type
+    #[inline]
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.skip_reply()?;
+        visitor.visit_unit()
+    }
+}
+
+/// The elements of a bulk reply, read as a sequence or as alternating keys and values
+struct Elements<'a, R> {
+    de: &'a mut Deserializer<R>,
+    remaining: usize,
+}
+
+impl<'a, 'de, R> Elements<'a, R>
+where
+    R: Read<'de>,
+{
+    fn skip_rest(&mut self) -> Result<()> {
+        while self.remaining > 0 {
+            self.remaining -= 1;
+            self.de.skip_reply()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, 'b, 'de, R> de::SeqAccess<'de> for &'b mut Elements<'a, R>
+where
+    R: Read<'de>,
+{
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'a, 'b, 'de, R> de::MapAccess<'de> for &'b mut Elements<'a, R>
+where
+    R: Read<'de>,
+{
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        if self.remaining == 0 {
+            return Err(Error::Decode(crate::decode::Error::EndOfStream));
+        }
+
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining / 2)
+    }
+}
+
+/// An enum variant named by a string reply; variants carry no content
+struct Variant<'a, R> {
+    de: &'a mut Deserializer<R>,
+}
+
+impl<'a, 'de, R> de::EnumAccess<'de> for Variant<'a, R>
+where
+    R: Read<'de>,
+{
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self)>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(&mut *self.de)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'a, 'de, R> de::VariantAccess<'de> for Variant<'a, R>
+where
+    R: Read<'de>,
+{
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        Ok(seed.deserialize(nil())?)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        Ok(de::Deserializer::deserialize_any(nil(), visitor)?)
+    }
+
+    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        Ok(de::Deserializer::deserialize_any(nil(), visitor)?)
+    }
+}
+
+/// The (missing) content of a variant, read the same way `decode::Deserializer` reads it
+fn nil() -> crate::decode::Deserializer<'static> {
+    crate::decode::Deserializer::new(Value::Nil)
+}
@@ -0,0 +1,812 @@
+use redis::Value;
+use serde::ser::{self, Serialize};
+use std::convert::TryFrom;
+use std::io;
+
+use super::{Error, Result};
+use crate::encode::{self, HashFieldsSerializer};
+
+/// Serialize `T` as a RESP2 reply into `writer`
+///
+/// The reply is what `encode::to_redis_value` builds, written in the Redis protocol format:
+/// scalars are bulk strings, `None` is a nil bulk string, sequences are arrays and structs and
+/// maps are arrays of alternating keys and values. Use `Serializer::resp3` for RESP3 replies.
+pub fn to_writer<W, T>(writer: W, value: &T) -> Result<()>
+where
+    W: io::Write,
+    T: Serialize + ?Sized,
+{
+    value.serialize(&mut Serializer::new(writer))
+}
+
+/// Serialize `T` as a RESP2 reply into a new `Vec`
+pub fn to_vec<T>(value: &T) -> Result<Vec<u8>>
+where
+    T: Serialize + ?Sized,
+{
+    let mut out = Vec::new();
+    to_writer(&mut out, value)?;
+    Ok(out)
+}
+
+/// Serialize `T` as a command into `writer`, in the form clients send commands to Redis
+///
+/// `T` is flattened into the command's arguments the way `redis::ToRedisArgs` flattens them:
+/// sequences and tuples contribute each element, structs and maps their `field value` pairs, and
+/// `None` nothing at all. `("HSET", "user:1", &user)` writes an `HSET` of all of `user`'s fields.
+/// As in `encode::to_hash_fields`, field values must be scalars; a field holding a sequence or
+/// struct is an `encode::Error::ExpectedHash` rather than a run of misaligned arguments.
+pub fn command_to_writer<W, T>(mut writer: W, args: &T) -> Result<()>
+where
+    W: io::Write,
+    T: Serialize + ?Sized,
+{
+    let mut flat = Args::default();
+    args.serialize(&mut flat)?;
+
+    let args: Vec<&[u8]> = flat.args.iter().map(Vec::as_slice).collect();
+    write_command(&mut writer, &args).map_err(Error::Io)
+}
+
+/// Serialize `T` as a command into a new `Vec`
+pub fn command_to_vec<T>(args: &T) -> Result<Vec<u8>>
+where
+    T: Serialize + ?Sized,
+{
+    let mut out = Vec::new();
+    command_to_writer(&mut out, args)?;
+    Ok(out)
+}
+
+/// Write a command array of raw arguments
+pub(crate) fn write_command<W>(writer: &mut W, args: &[&[u8]]) -> io::Result<()>
+where
+    W: io::Write,
+{
+    write!(writer, "*{}\r\n", args.len())?;
+    for arg in args {
+        write!(writer, "${}\r\n", arg.len())?;
+        writer.write_all(arg)?;
+        writer.write_all(b"\r\n")?;
+    }
+
+    Ok(())
+}
+
+fn write_header<W>(writer: &mut W, tag: u8, len: usize) -> Result<()>
+where
+    W: io::Write,
+{
+    write!(writer, "{}{}\r\n", tag as char, len).map_err(Error::Io)
+}
+
+fn write_blob<W>(writer: &mut W, bytes: &[u8]) -> Result<()>
+where
+    W: io::Write,
+{
+    write_header(writer, b'$', bytes.len())?;
+    writer.write_all(bytes).map_err(Error::Io)?;
+    writer.write_all(b"\r\n").map_err(Error::Io)
+}
+
+/// Serializes values as RESP replies into an `io::Write`
+///
+/// RESP2 output is the value `encode::Serializer` builds. RESP3 output keeps the types RESP2
+/// flattens into strings: booleans, integers, doubles, nulls and maps are written with their own
+/// RESP3 types, and integers beyond `i64` as big numbers. 128-bit integers are only accepted in
+/// RESP3, since `encode::Serializer` has no representation for them. Either decodes back through
+/// `resp::from_slice`.
+///
+/// Arrays and maps whose length serde does not give up front are buffered until they end.
+#[derive(Debug)]
+pub struct Serializer<W> {
+    writer: W,
+    resp3: bool,
+}
+
+impl<W> Serializer<W>
+where
+    W: io::Write,
+{
+    pub fn new(writer: W) -> Self {
+        Serializer {
+            writer,
+            resp3: false,
+        }
+    }
+
+    /// A serializer that writes RESP3 replies
+    pub fn resp3(writer: W) -> Self {
+        Serializer {
+            writer,
+            resp3: true,
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_line(&mut self, tag: u8, line: &str) -> Result<()> {
+        write!(self.writer, "{}{}\r\n", tag as char, line).map_err(Error::Io)
+    }
+
+    fn write_nil(&mut self) -> Result<()> {
+        let nil: &[u8] = if self.resp3 { b"_\r\n" } else { b"$-1\r\n" };
+        self.writer.write_all(nil).map_err(Error::Io)
+    }
+
+    fn write_aggregate(&mut self, aggregate: Aggregate, elements: usize) -> Result<()> {
+        match aggregate {
+            Aggregate::Map if self.resp3 => write_header(&mut self.writer, b'%', elements / 2),
+            _ => write_header(&mut self.writer, b'*', elements),
+        }
+    }
+
+    fn compound(
+        &mut self,
+        aggregate: Aggregate,
+        elements: Option<usize>,
+    ) -> Result<Compound<'_, W>> {
+        let state = match elements {
+            Some(len) => {
+                self.write_aggregate(aggregate, len)?;
+                State::Streaming { remaining: len }
+            }
+            None => State::Buffered {
+                buffer: Vec::new(),
+                count: 0,
+            },
+        };
+
+        Ok(Compound {
+            ser: self,
+            aggregate,
+            state,
+        })
+    }
+}
+
+macro_rules! impl_num {
+    ($ty:ty, $serialize_method:ident) => {
+        #[inline]
+        fn $serialize_method(self, v: $ty) -> Result<()> {
+            if self.resp3 {
+                self.write_line(b':', &v.to_string())
+            } else {
+                write_blob(&mut self.writer, v.to_string().as_bytes())
+            }
+        }
+    };
+}
+
+macro_rules! impl_big_num {
+    ($ty:ty, $serialize_method:ident) => {
+        #[inline]
+        fn $serialize_method(self, v: $ty) -> Result<()> {
+            if !self.resp3 {
+                // RESP2 replies are the values `encode::Serializer` builds, so refuse what it does
+                encode::Serializer.$serialize_method(v)?;
+                write_blob(&mut self.writer, v.to_string().as_bytes())
+            } else if i64::try_from(v).is_ok() {
+                self.write_line(b':', &v.to_string())
+            } else {
+                self.write_line(b'(', &v.to_string())
+            }
+        }
+    };
+}
+
+macro_rules! impl_float {
+    ($ty:ty, $serialize_method:ident) => {
+        #[inline]
+        fn $serialize_method(self, v: $ty) -> Result<()> {
+            let s = encode::float_to_string(v);
+
+            if self.resp3 {
+                self.write_line(b',', &s)
+            } else {
+                write_blob(&mut self.writer, s.as_bytes())
+            }
+        }
+    };
+}
+
+impl<'a, W> ser::Serializer for &'a mut Serializer<W>
+where
+    W: io::Write,
+{
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = Compound<'a, W>;
+    type SerializeTuple = Compound<'a, W>;
+    type SerializeTupleStruct = Compound<'a, W>;
+    type SerializeTupleVariant = ser::Impossible<(), Error>;
+    type SerializeMap = Compound<'a, W>;
+    type SerializeStruct = Compound<'a, W>;
+    type SerializeStructVariant = ser::Impossible<(), Error>;
+
+    #[inline]
+    fn serialize_bool(self, v: bool) -> Result<()> {
+        match (self.resp3, v) {
+            (true, true) => self.write_line(b'#', "t"),
+            (true, false) => self.write_line(b'#', "f"),
+            (false, true) => write_blob(&mut self.writer, b"1"),
+            (false, false) => write_blob(&mut self.writer, b"0"),
+        }
+    }
+
+    impl_num!(i8, serialize_i8);
+    impl_num!(i16, serialize_i16);
+    impl_num!(i32, serialize_i32);
+    impl_num!(i64, serialize_i64);
+
+    impl_num!(u8, serialize_u8);
+    impl_num!(u16, serialize_u16);
+    impl_num!(u32, serialize_u32);
+
+    impl_big_num!(u64, serialize_u64);
+    impl_big_num!(i128, serialize_i128);
+    impl_big_num!(u128, serialize_u128);
+
+    impl_float!(f32, serialize_f32);
+    impl_float!(f64, serialize_f64);
+
+    #[inline]
+    fn serialize_char(self, v: char) -> Result<()> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    #[inline]
+    fn serialize_str(self, v: &str) -> Result<()> {
+        write_blob(&mut self.writer, v.as_bytes())
+    }
+
+    #[inline]
+    fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+        write_blob(&mut self.writer, v)
+    }
+
+    #[inline]
+    fn serialize_none(self) -> Result<()> {
+        self.write_nil()
+    }
+
+    #[inline]
+    fn serialize_some<T>(self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_unit(self) -> Result<()> {
+        self.write_nil()
+    }
+
+    #[inline]
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        self.serialize_unit()
+    }
+
+    #[inline]
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<()> {
+        self.serialize_str(variant)
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(encode::Error::UnsupportedType("newtype variant").into())
+    }
+
+    #[inline]
+    fn serialize_seq(self, len: Option<usize>) -> Result<Compound<'a, W>> {
+        self.compound(Aggregate::Array, len)
+    }
+
+    #[inline]
+    fn serialize_tuple(self, len: usize) -> Result<Compound<'a, W>> {
+        self.serialize_seq(Some(len))
+    }
+
+    #[inline]
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Compound<'a, W>> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(encode::Error::UnsupportedType("tuple variant").into())
+    }
+
+    #[inline]
+    fn serialize_map(self, len: Option<usize>) -> Result<Compound<'a, W>> {
+        self.compound(Aggregate::Map, len.map(|len| len * 2))
+    }
+
+    #[inline]
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Compound<'a, W>> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(encode::Error::UnsupportedType("struct variant").into())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Aggregate {
+    Array,
+    Map,
+}
+
+#[derive(Debug)]
+enum State {
+    /// The header is written; elements go straight to the writer
+    Streaming { remaining: usize },
+    /// The length is unknown until the end, so elements are collected first
+    Buffered { buffer: Vec<u8>, count: usize },
+}
+
+/// Writes the elements of an array, or alternating keys and values of a map
+#[derive(Debug)]
+pub struct Compound<'a, W> {
+    ser: &'a mut Serializer<W>,
+    aggregate: Aggregate,
+    state: State,
+}
+
+impl<'a, W> Compound<'a, W>
+where
+    W: io::Write,
+{
+    fn push<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        match self.state {
+            State::Streaming { ref mut remaining } => {
+                *remaining = remaining.checked_sub(1).ok_or_else(|| {
+                    <Error as ser::Error>::custom("more elements than the given length")
+                })?;
+                value.serialize(&mut *self.ser)
+            }
+            State::Buffered {
+                ref mut buffer,
+                ref mut count,
+            } => {
+                *count += 1;
+                let mut ser = Serializer {
+                    writer: buffer,
+                    resp3: self.ser.resp3,
+                };
+                value.serialize(&mut ser)
+            }
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        match self.state {
+            State::Streaming { remaining: 0 } => Ok(()),
+            State::Streaming { .. } => Err(<Error as ser::Error>::custom(
+                "fewer elements than the given length",
+            )),
+            State::Buffered { buffer, count } => {
+                self.ser.write_aggregate(self.aggregate, count)?;
+                self.ser.writer.write_all(&buffer).map_err(Error::Io)
+            }
+        }
+    }
+}
+
+impl<'a, W> ser::SerializeSeq for Compound<'a, W>
+where
+    W: io::Write,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<'a, W> ser::SerializeTuple for Compound<'a, W>
+where
+    W: io::Write,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<'a, W> ser::SerializeTupleStruct for Compound<'a, W>
+where
+    W: io::Write,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<'a, W> ser::SerializeMap for Compound<'a, W>
+where
+    W: io::Write,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(key)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<'a, W> ser::SerializeStruct for Compound<'a, W>
+where
+    W: io::Write,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(key)?;
+        self.push(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+/// Collects command arguments, flattening sequences, structs and maps
+#[derive(Debug, Default)]
+struct Args {
+    args: Vec<Vec<u8>>,
+}
+
+impl Args {
+    fn push(&mut self, value: Value) {
+        match value {
+            Value::Nil => {}
+            Value::Int(i) => self.args.push(i.to_string().into_bytes()),
+            Value::Data(bytes) => self.args.push(bytes),
+            Value::Bulk(values) => {
+                for value in values {
+                    self.push(value);
+                }
+            }
+            Value::Status(status) => self.args.push(status.into_bytes()),
+            Value::Okay => self.args.push(b"OK".to_vec()),
+        }
+    }
+}
+
+macro_rules! forward_scalar {
+    ($ty:ty, $serialize_method:ident) => {
+        #[inline]
+        fn $serialize_method(self, v: $ty) -> encode::Result<()> {
+            self.push(encode::Serializer.$serialize_method(v)?);
+            Ok(())
+        }
+    };
+}
+
+impl<'a> ser::Serializer for &'a mut Args {
+    type Ok = ();
+    type Error = encode::Error;
+
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = ser::Impossible<(), encode::Error>;
+    type SerializeMap = ArgPairs<'a>;
+    type SerializeStruct = ArgPairs<'a>;
+    type SerializeStructVariant = ser::Impossible<(), encode::Error>;
+
+    forward_scalar!(bool, serialize_bool);
+    forward_scalar!(i8, serialize_i8);
+    forward_scalar!(i16, serialize_i16);
+    forward_scalar!(i32, serialize_i32);
+    forward_scalar!(i64, serialize_i64);
+    forward_scalar!(u8, serialize_u8);
+    forward_scalar!(u16, serialize_u16);
+    forward_scalar!(u32, serialize_u32);
+    forward_scalar!(u64, serialize_u64);
+    forward_scalar!(i128, serialize_i128);
+    forward_scalar!(u128, serialize_u128);
+    forward_scalar!(f32, serialize_f32);
+    forward_scalar!(f64, serialize_f64);
+    forward_scalar!(char, serialize_char);
+    forward_scalar!(&str, serialize_str);
+    forward_scalar!(&[u8], serialize_bytes);
+
+    #[inline]
+    fn serialize_none(self) -> encode::Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_some<T>(self, value: &T) -> encode::Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_unit(self) -> encode::Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_unit_struct(self, _name: &'static str) -> encode::Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> encode::Result<()> {
+        self.serialize_str(variant)
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> encode::Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> encode::Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(encode::Error::UnsupportedType("newtype variant"))
+    }
+
+    #[inline]
+    fn serialize_seq(self, _len: Option<usize>) -> encode::Result<Self> {
+        Ok(self)
+    }
+
+    #[inline]
+    fn serialize_tuple(self, _len: usize) -> encode::Result<Self> {
+        Ok(self)
+    }
+
+    #[inline]
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> encode::Result<Self> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> encode::Result<Self::SerializeTupleVariant> {
+        Err(encode::Error::UnsupportedType("tuple variant"))
+    }
+
+    #[inline]
+    fn serialize_map(self, len: Option<usize>) -> encode::Result<ArgPairs<'a>> {
+        Ok(ArgPairs {
+            args: self,
+            fields: HashFieldsSerializer::new(len.unwrap_or(0)),
+        })
+    }
+
+    #[inline]
+    fn serialize_struct(self, _name: &'static str, len: usize) -> encode::Result<ArgPairs<'a>> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> encode::Result<Self::SerializeStructVariant> {
+        Err(encode::Error::UnsupportedType("struct variant"))
+    }
+}
+
+impl ser::SerializeSeq for &mut Args {
+    type Ok = ();
+    type Error = encode::Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> encode::Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> encode::Result<()> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for &mut Args {
+    type Ok = ();
+    type Error = encode::Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> encode::Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> encode::Result<()> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut Args {
+    type Ok = ();
+    type Error = encode::Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> encode::Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> encode::Result<()> {
+        Ok(())
+    }
+}
+
+/// Collects the `field value` pairs of a struct or map argument
+///
+/// Values must be scalars, as in `encode::to_hash_fields`; pairs whose value is `None` are left
+/// out.
+struct ArgPairs<'a> {
+    args: &'a mut Args,
+    fields: HashFieldsSerializer,
+}
+
+impl<'a> ArgPairs<'a> {
+    fn finish(self) -> encode::Result<()> {
+        let ArgPairs { args, fields } = self;
+        for (field, value) in ser::SerializeMap::end(fields)? {
+            if let Value::Data(value) = value {
+                args.args.push(field);
+                args.args.push(value);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeMap for ArgPairs<'a> {
+    type Ok = ();
+    type Error = encode::Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> encode::Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        ser::SerializeMap::serialize_key(&mut self.fields, key)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> encode::Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        ser::SerializeMap::serialize_value(&mut self.fields, value)
+    }
+
+    fn end(self) -> encode::Result<()> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeStruct for ArgPairs<'a> {
+    type Ok = ();
+    type Error = encode::Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> encode::Result<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        ser::SerializeStruct::serialize_field(&mut self.fields, key, value)
+    }
+
+    fn end(self) -> encode::Result<()> {
+        self.finish()
+    }
+}
@@ -0,0 +1,175 @@
+//! Working directly with the Redis protocol (RESP)
+//!
+//! `from_slice` decodes raw reply bytes without building a `redis::Value` tree first. Values
+//! are read as the visitor asks for them, and strings and bytes are borrowed from the input, so
+//! `T` may hold `&str` and `&[u8]` fields. Types decode the same way they do from the equivalent
+//! `redis::Value` with `from_redis_value`.
+//!
+//! RESP3 replies (from connections that sent `HELLO 3`) are read natively: maps decode through
+//! `visit_map` without the flat pair convention, sets and push messages as sequences, booleans
+//! and doubles into `bool` and floats, big numbers into integers (including `i128`/`u128`) or
+//! strings, and verbatim strings into strings without their format prefix. Attributes are
+//! skipped. `parse_value` reads a reply into the owned [`Value`] model instead.
+//!
+//! `from_reader` reads a reply incrementally from an `io::BufRead`, such as a buffered socket or
+//! a dump on disk. Elements are deserialized as they arrive and only the string being decoded is
+//! buffered, so memory stays bounded however large the reply. Strings are copied rather than
+//! borrowed, so `T` must own its data.
+//!
+//! The other direction writes RESP: `to_writer` and `to_vec` write a value as a RESP2 reply, in
+//! the shape `encode::to_redis_value` gives it, and `Serializer::resp3` writes RESP3 replies.
+//! `command_to_writer` and `command_to_vec` write a command array, the format clients send and
+//! `redis-cli --pipe` reads.
+//!
+//! ```
+//! # #[macro_use] extern crate serde_derive;
+//! #[derive(Deserialize, Debug, PartialEq)]
+//! struct User<'a> {
+//!     name: &'a str,
+//!     visits: u32,
+//! }
+//!
+//! let reply = b"*4\r\n$4\r\nname\r\n$3\r\nada\r\n$6\r\nvisits\r\n$1\r\n3\r\n";
+//! let user: User = serde_redis::resp::from_slice(reply).unwrap();
+//!
+//! assert_eq!(User { name: "ada", visits: 3 }, user);
+//! # fn main() {}
+//! ```
+//!
+//! ```
+//! let cmd = serde_redis::resp::command_to_vec(&("SET", "visits", 3)).unwrap();
+//!
+//! assert_eq!(&b"*3\r\n$3\r\nSET\r\n$6\r\nvisits\r\n$1\r\n3\r\n"[..], &cmd[..]);
+//! ```
+use serde::{de, ser};
+use std::fmt::{self, Display};
+use std::{error, io, num, str};
+
+mod decode;
+mod encode;
+mod read;
+mod value;
+
+pub use self::decode::{from_reader, from_slice, Deserializer};
+pub(crate) use self::encode::write_command;
+pub use self::encode::{command_to_vec, command_to_writer, to_vec, to_writer, Serializer};
+pub use self::read::{IoRead, Read, SliceRead};
+pub use self::value::{parse_value, Value};
+
+/// Error produced while reading or writing RESP
+#[derive(Debug)]
+pub enum Error {
+    /// The input ended in the middle of a value
+    Eof,
+    /// The input is not valid RESP; holds the byte offset and what was wrong
+    Syntax(usize, String),
+    /// The input holds an error reply, such as `-ERR unknown command`
+    Server(String),
+    /// Input was left over after the value; holds the offset where it starts
+    TrailingBytes(usize),
+    /// Reading from the underlying reader failed
+    Io(io::Error),
+    /// A value was read but does not fit the requested type
+    Decode(crate::decode::Error),
+    /// A value has no RESP representation
+    Encode(crate::encode::Error),
+}
+
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Decode(ref err) => Some(err),
+            Error::Encode(ref err) => Some(err),
+            Error::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Eof => write!(f, "unexpected end of input"),
+            Error::Syntax(offset, ref msg) => write!(f, "{} at byte {}", msg, offset),
+            Error::Server(ref msg) => write!(f, "error reply: {}", msg),
+            Error::TrailingBytes(offset) => write!(f, "trailing bytes at byte {}", offset),
+            Error::Io(ref err) => write!(f, "{}", err),
+            Error::Decode(ref err) => write!(f, "{}", err),
+            Error::Encode(ref err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Decode(crate::decode::Error::custom(msg))
+    }
+
+    fn unknown_variant(variant: &str, expected: &'static [&'static str]) -> Self {
+        Error::Decode(crate::decode::Error::unknown_variant(variant, expected))
+    }
+
+    fn unknown_field(field: &str, expected: &'static [&'static str]) -> Self {
+        Error::Decode(crate::decode::Error::unknown_field(field, expected))
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Error::Decode(crate::decode::Error::missing_field(field))
+    }
+
+    fn duplicate_field(field: &'static str) -> Self {
+        Error::Decode(crate::decode::Error::duplicate_field(field))
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Encode(crate::encode::Error::custom(msg))
+    }
+}
+
+impl From<crate::decode::Error> for Error {
+    fn from(err: crate::decode::Error) -> Error {
+        Error::Decode(err)
+    }
+}
+
+impl From<crate::encode::Error> for Error {
+    fn from(err: crate::encode::Error) -> Error {
+        Error::Encode(err)
+    }
+}
+
+impl From<str::Utf8Error> for Error {
+    fn from(err: str::Utf8Error) -> Error {
+        Error::Decode(err.into())
+    }
+}
+
+impl From<num::ParseIntError> for Error {
+    fn from(err: num::ParseIntError) -> Error {
+        Error::Decode(err.into())
+    }
+}
+
+impl From<num::ParseFloatError> for Error {
+    fn from(err: num::ParseFloatError) -> Error {
+        Error::Decode(err.into())
+    }
+}
+
+impl From<Error> for redis::RedisError {
+    fn from(err: Error) -> redis::RedisError {
+        if let Error::Encode(err) = err {
+            return err.into();
+        }
+
+        redis::RedisError::from((
+            redis::ErrorKind::TypeError,
+            "Response could not be deserialized",
+            err.to_string(),
+        ))
+    }
+}
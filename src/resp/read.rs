@@ -0,0 +1,259 @@
+use std::fmt;
+use std::io::{BufRead, Read as _};
+use std::ops::Deref;
+use std::str;
+
+use super::{Error, Result};
+
+/// Bytes taken from the input: borrowed from it when it is a slice, otherwise copied into the
+/// deserializer's scratch buffer
+#[doc(hidden)]
+pub enum Reference<'b, 'c, T: ?Sized> {
+    Borrowed(&'b T),
+    Copied(&'c T),
+}
+
+impl<'b, 'c, T: ?Sized> Deref for Reference<'b, 'c, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match *self {
+            Reference::Borrowed(value) => value,
+            Reference::Copied(value) => value,
+        }
+    }
+}
+
+impl<'b, 'c, T: ?Sized> Clone for Reference<'b, 'c, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'b, 'c, T: ?Sized> Copy for Reference<'b, 'c, T> {}
+
+impl<'b, 'c, T> fmt::Debug for Reference<'b, 'c, T>
+where
+    T: ?Sized + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<'b, 'c> Reference<'b, 'c, [u8]> {
+    pub(super) fn slice(self, start: usize, end: usize) -> Self {
+        match self {
+            Reference::Borrowed(bytes) => Reference::Borrowed(&bytes[start..end]),
+            Reference::Copied(bytes) => Reference::Copied(&bytes[start..end]),
+        }
+    }
+
+    pub(super) fn into_str(self) -> Result<Reference<'b, 'c, str>> {
+        Ok(match self {
+            Reference::Borrowed(bytes) => Reference::Borrowed(str::from_utf8(bytes)?),
+            Reference::Copied(bytes) => Reference::Copied(str::from_utf8(bytes)?),
+        })
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Input the RESP `Deserializer` can read from
+///
+/// Implemented by `SliceRead`, which borrows strings from the input, and `IoRead`, which copies
+/// them. This trait is sealed.
+pub trait Read<'de>: private::Sealed {
+    /// Look at up to `n` upcoming bytes without consuming them; fewer only at the end of input
+    #[doc(hidden)]
+    fn peek(&mut self, n: usize) -> Result<&[u8]>;
+
+    /// Consume `n` bytes returned by `peek`
+    #[doc(hidden)]
+    fn consume(&mut self, n: usize);
+
+    /// Offset of the next byte from the start of the input
+    #[doc(hidden)]
+    fn position(&self) -> usize;
+
+    /// Read through the next CRLF, returning the line without it
+    #[doc(hidden)]
+    fn read_line<'s>(&'s mut self, scratch: &'s mut Vec<u8>) -> Result<Reference<'de, 's, [u8]>>;
+
+    /// Read `len` bytes followed by a CRLF, returning the bytes without it
+    #[doc(hidden)]
+    fn read_blob<'s>(
+        &'s mut self,
+        len: usize,
+        scratch: &'s mut Vec<u8>,
+    ) -> Result<Reference<'de, 's, [u8]>>;
+}
+
+/// Reads from a byte slice
+#[derive(Debug)]
+pub struct SliceRead<'de> {
+    slice: &'de [u8],
+    index: usize,
+}
+
+impl<'de> SliceRead<'de> {
+    pub fn new(slice: &'de [u8]) -> Self {
+        SliceRead { slice, index: 0 }
+    }
+}
+
+impl<'de> private::Sealed for SliceRead<'de> {}
+
+impl<'de> Read<'de> for SliceRead<'de> {
+    fn peek(&mut self, n: usize) -> Result<&[u8]> {
+        let end = (self.index + n).min(self.slice.len());
+        Ok(&self.slice[self.index..end])
+    }
+
+    fn consume(&mut self, n: usize) {
+        self.index += n;
+    }
+
+    fn position(&self) -> usize {
+        self.index
+    }
+
+    fn read_line<'s>(&'s mut self, _scratch: &'s mut Vec<u8>) -> Result<Reference<'de, 's, [u8]>> {
+        let rest = &self.slice[self.index..];
+        let len = rest
+            .windows(2)
+            .position(|w| w == b"\r\n")
+            .ok_or(Error::Eof)?;
+
+        self.index += len + 2;
+        Ok(Reference::Borrowed(&rest[..len]))
+    }
+
+    fn read_blob<'s>(
+        &'s mut self,
+        len: usize,
+        _scratch: &'s mut Vec<u8>,
+    ) -> Result<Reference<'de, 's, [u8]>> {
+        let end = self.index + len;
+        if self.slice.len() < end + 2 {
+            return Err(Error::Eof);
+        }
+        if &self.slice[end..end + 2] != b"\r\n" {
+            return Err(Error::Syntax(
+                end,
+                "expected CRLF after bulk string".to_owned(),
+            ));
+        }
+
+        let blob = &self.slice[self.index..end];
+        self.index = end + 2;
+        Ok(Reference::Borrowed(blob))
+    }
+}
+
+/// Reads incrementally from an `io::BufRead`
+///
+/// Only the string being decoded is held in memory, so aggregates of any size can be read.
+#[derive(Debug)]
+pub struct IoRead<R> {
+    reader: R,
+    /// Bytes taken from the reader by `peek` but not consumed yet
+    lookahead: Vec<u8>,
+    position: usize,
+}
+
+impl<R> IoRead<R>
+where
+    R: BufRead,
+{
+    pub fn new(reader: R) -> Self {
+        IoRead {
+            reader,
+            lookahead: Vec::with_capacity(2),
+            position: 0,
+        }
+    }
+
+    /// Move the lookahead into `scratch`, so a read can continue after it
+    fn start_scratch(&mut self, scratch: &mut Vec<u8>) {
+        scratch.clear();
+        scratch.append(&mut self.lookahead);
+    }
+}
+
+impl<R> private::Sealed for IoRead<R> {}
+
+impl<'de, R> Read<'de> for IoRead<R>
+where
+    R: BufRead,
+{
+    fn peek(&mut self, n: usize) -> Result<&[u8]> {
+        while self.lookahead.len() < n {
+            let buf = self.reader.fill_buf().map_err(Error::Io)?;
+            if buf.is_empty() {
+                break;
+            }
+
+            let take = (n - self.lookahead.len()).min(buf.len());
+            self.lookahead.extend_from_slice(&buf[..take]);
+            self.reader.consume(take);
+        }
+
+        let end = n.min(self.lookahead.len());
+        Ok(&self.lookahead[..end])
+    }
+
+    fn consume(&mut self, n: usize) {
+        self.lookahead.drain(..n);
+        self.position += n;
+    }
+
+    fn position(&self) -> usize {
+        self.position
+    }
+
+    fn read_line<'s>(&'s mut self, scratch: &'s mut Vec<u8>) -> Result<Reference<'de, 's, [u8]>> {
+        self.start_scratch(scratch);
+        while !scratch.ends_with(b"\r\n") {
+            if self.reader.read_until(b'\n', scratch).map_err(Error::Io)? == 0 {
+                return Err(Error::Eof);
+            }
+        }
+
+        self.position += scratch.len();
+        scratch.truncate(scratch.len() - 2);
+        Ok(Reference::Copied(&scratch[..]))
+    }
+
+    fn read_blob<'s>(
+        &'s mut self,
+        len: usize,
+        scratch: &'s mut Vec<u8>,
+    ) -> Result<Reference<'de, 's, [u8]>> {
+        self.start_scratch(scratch);
+
+        let wanted = len + 2;
+        if scratch.len() < wanted {
+            let missing = (wanted - scratch.len()) as u64;
+            (&mut self.reader)
+                .take(missing)
+                .read_to_end(scratch)
+                .map_err(Error::Io)?;
+        }
+        if scratch.len() < wanted {
+            return Err(Error::Eof);
+        }
+        if &scratch[len..] != b"\r\n" {
+            return Err(Error::Syntax(
+                self.position + len,
+                "expected CRLF after bulk string".to_owned(),
+            ));
+        }
+
+        self.position += wanted;
+        scratch.truncate(len);
+        Ok(Reference::Copied(&scratch[..]))
+    }
+}
@@ -0,0 +1,96 @@
+use super::decode::{Deserializer, Reply};
+use super::read::Read;
+use super::Result;
+
+/// Upper bound on preallocated elements, so a bogus length cannot exhaust memory up front
+const MAX_PREALLOC: usize = 4096;
+
+/// A reply in the RESP3 data model
+///
+/// A superset of `redis::Value`: besides the RESP2 types it keeps maps, sets, doubles, booleans,
+/// big numbers, verbatim strings and push messages apart. Attributes are dropped.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Nil,
+    Status(String),
+    Int(i64),
+    Data(Vec<u8>),
+    Bulk(Vec<Value>),
+    Map(Vec<(Value, Value)>),
+    Set(Vec<Value>),
+    Push(Vec<Value>),
+    Double(f64),
+    Boolean(bool),
+    /// The decimal digits of an integer too large for `Int`
+    BigNumber(String),
+    /// A string with its three letter format, such as `txt` or `mkd`
+    Verbatim {
+        format: String,
+        text: String,
+    },
+}
+
+impl From<redis::Value> for Value {
+    fn from(value: redis::Value) -> Value {
+        match value {
+            redis::Value::Nil => Value::Nil,
+            redis::Value::Int(i) => Value::Int(i),
+            redis::Value::Data(bytes) => Value::Data(bytes),
+            redis::Value::Bulk(values) => {
+                Value::Bulk(values.into_iter().map(Value::from).collect())
+            }
+            redis::Value::Status(status) => Value::Status(status),
+            redis::Value::Okay => Value::Status("OK".to_owned()),
+        }
+    }
+}
+
+/// Parse a single RESP2 or RESP3 reply into a `Value`
+pub fn parse_value(input: &[u8]) -> Result<Value> {
+    let mut de = Deserializer::from_slice(input);
+    let value = read_value(&mut de)?;
+    de.end()?;
+    Ok(value)
+}
+
+fn read_value<'de, R>(de: &mut Deserializer<R>) -> Result<Value>
+where
+    R: Read<'de>,
+{
+    Ok(match de.next_reply()? {
+        Reply::Nil => Value::Nil,
+        Reply::Status(status) => Value::Status((*status.into_str()?).to_owned()),
+        Reply::Int(i) => Value::Int(i),
+        Reply::Data(bytes) => Value::Data(bytes.to_vec()),
+        Reply::Bulk(len) => Value::Bulk(read_values(de, len)?),
+        Reply::Set(len) => Value::Set(read_values(de, len)?),
+        Reply::Push(len) => Value::Push(read_values(de, len)?),
+        Reply::Map(len) => Value::Map(de.nested(|de| {
+            let mut pairs = Vec::with_capacity(len.min(MAX_PREALLOC));
+            for _ in 0..len {
+                pairs.push((read_value(de)?, read_value(de)?));
+            }
+            Ok(pairs)
+        })?),
+        Reply::Double(d) => Value::Double(d),
+        Reply::Boolean(b) => Value::Boolean(b),
+        Reply::BigNumber(digits) => Value::BigNumber((*digits).to_owned()),
+        Reply::Verbatim(format, text) => Value::Verbatim {
+            format: (*format).to_owned(),
+            text: (*text.into_str()?).to_owned(),
+        },
+    })
+}
+
+fn read_values<'de, R>(de: &mut Deserializer<R>, len: usize) -> Result<Vec<Value>>
+where
+    R: Read<'de>,
+{
+    de.nested(|de| {
+        let mut values = Vec::with_capacity(len.min(MAX_PREALLOC));
+        for _ in 0..len {
+            values.push(read_value(de)?);
+        }
+        Ok(values)
+    })
+}
@@ -0,0 +1,594 @@
+//! Introspection of the hash layout a `Deserialize` type expects
+//!
+//! [`fields`] and [`shape`] drive a type's `Deserialize` impl with a tracing deserializer that
+//! records what was asked for instead of reading any data. Structs report the `fields` slice serde
+//! passes to `deserialize_struct`, enums report the `variants` slice passed to `deserialize_enum`
+//! with the content of each variant, and everything else is recorded by the `deserialize_*`
+//! method it calls.
+//!
+//! ```
+//! # #[macro_use] extern crate serde_derive;
+//! use serde_redis::schema::{self, Shape};
+//!
+//! #[derive(Deserialize)]
+//! struct User {
+//!     name: String,
+//!     age: Option<u8>,
+//! }
+//!
+//! let fields = schema::fields::<User>().unwrap();
+//! assert_eq!(fields[0].name, "name");
+//! assert_eq!(fields[1].shape, Shape::Option(Box::new(Shape::Int)));
+//! # fn main() {}
+//! ```
+//!
+//! Tracing feeds each value a placeholder (`0`, `""`, `false`, the first enum variant, one
+//! element per sequence), so types whose `Deserialize` impl rejects those values cannot be
+//! traced. Structs using `#[serde(flatten)]` or `#[serde(alias)]` cannot be traced either. Each
+//! enum variant is traced by running the type's `Deserialize` impl again with that variant
+//! selected.
+use serde::de::{self, Deserialize, DeserializeSeed, IntoDeserializer, Visitor};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::mem;
+
+use crate::decode::{Error, Result};
+
+/// How deep nested options, sequences and maps are followed before tracing stops
+///
+/// This keeps recursive types such as `struct Node { next: Option<Box<Node>> }` finite.
+const MAX_DEPTH: usize = 32;
+
+/// A named field of a struct
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: &'static str,
+    pub shape: Shape,
+}
+
+/// A variant of an enum and the shape of its content
+///
+/// Unit variants are `Shape::Unit`, newtype variants the shape of the wrapped value, tuple
+/// variants `Shape::Tuple` and struct variants `Shape::Struct` named after the variant.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    pub name: &'static str,
+    pub shape: Shape,
+}
+
+/// The shape of a value as requested from the deserializer
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Bool,
+    /// Any signed or unsigned integer
+    Int,
+    /// `f32` or `f64`
+    Float,
+    Char,
+    Str,
+    Bytes,
+    Unit,
+    Option(Box<Shape>),
+    Seq(Box<Shape>),
+    Tuple(Vec<Shape>),
+    Map(Box<Shape>, Box<Shape>),
+    Struct {
+        name: &'static str,
+        fields: Vec<Field>,
+    },
+    Enum {
+        name: &'static str,
+        variants: Vec<Variant>,
+    },
+    /// The type asked for a self-describing value, or tracing stopped at `MAX_DEPTH` or at an
+    /// enum nested in a variant of itself
+    Any,
+}
+
+impl Shape {
+    /// Whether a value of this shape may be left out of a hash
+    pub fn is_optional(&self) -> bool {
+        matches!(self, Shape::Option(_))
+    }
+}
+
+/// Trace the shape of `T`
+pub fn shape<'de, T>() -> Result<Shape>
+where
+    T: Deserialize<'de>,
+{
+    let mut pending = Vec::new();
+    let mut shape = trace::<T>(&[], &mut pending)?;
+
+    while let Some(variant) = pending.pop() {
+        let mut traced = trace::<T>(&variant, &mut pending)?;
+        if let (Some(slot), Some(content)) = (at(&mut shape, &variant), at(&mut traced, &variant)) {
+            *slot = mem::replace(content, Shape::Any);
+        }
+    }
+
+    Ok(shape)
+}
+
+/// Trace the fields of struct `T`, including the shapes of nested structs
+///
+/// Types that do not deserialize as a struct return `Error::DeserializeNotSupported`.
+pub fn fields<'de, T>() -> Result<Vec<Field>>
+where
+    T: Deserialize<'de>,
+{
+    match shape::<T>()? {
+        Shape::Struct { fields, .. } => Ok(fields),
+        _ => Err(Error::DeserializeNotSupported),
+    }
+}
+
+/// A step from a shape to one nested in it
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Step {
+    Some,
+    Elem(usize),
+    Key,
+    Value,
+    Field(&'static str),
+    /// The variant at an index of the named enum
+    Variant(&'static str, usize),
+}
+
+/// State shared by one run of a type's `Deserialize` impl
+struct Trace {
+    /// Variants to select, by the path of their enum; other enums select their first variant
+    choices: HashMap<Vec<Step>, usize>,
+    /// Every enum met, by path, with its name and number of variants
+    enums: RefCell<Vec<(Vec<Step>, &'static str, usize)>>,
+}
+
+/// Trace `T` with the variants on the path to `target` selected, and queue the variants of the
+/// enums inside `target` that are still untraced
+fn trace<'de, T>(target: &[Step], pending: &mut Vec<Vec<Step>>) -> Result<Shape>
+where
+    T: Deserialize<'de>,
+{
+    let choices = target
+        .iter()
+        .enumerate()
+        .filter_map(|(i, step)| match *step {
+            Step::Variant(_, index) => Some((target[..i].to_vec(), index)),
+            _ => None,
+        })
+        .collect();
+    let trace = Trace {
+        choices,
+        enums: RefCell::new(Vec::new()),
+    };
+
+    let mut shape = Shape::Any;
+    T::deserialize(Tracer {
+        shape: &mut shape,
+        path: Vec::new(),
+        trace: &trace,
+    })?;
+
+    for (path, name, len) in trace.enums.into_inner() {
+        let recursive = path
+            .iter()
+            .any(|step| matches!(*step, Step::Variant(outer, _) if outer == name));
+        if !path.starts_with(target) || recursive {
+            continue;
+        }
+
+        for index in 1..len {
+            let mut variant = path.clone();
+            variant.push(Step::Variant(name, index));
+            pending.push(variant);
+        }
+    }
+
+    Ok(shape)
+}
+
+/// The shape at `path` inside `shape`
+fn at<'a>(shape: &'a mut Shape, path: &[Step]) -> Option<&'a mut Shape> {
+    let (step, rest) = match path.split_first() {
+        Some(split) => split,
+        None => return Some(shape),
+    };
+
+    let inner = match (step, shape) {
+        (Step::Some, Shape::Option(inner))
+        | (Step::Elem(_), Shape::Seq(inner))
+        | (Step::Key, Shape::Map(inner, _))
+        | (Step::Value, Shape::Map(_, inner)) => &mut **inner,
+        (Step::Elem(index), Shape::Tuple(shapes)) => shapes.get_mut(*index)?,
+        (Step::Field(name), Shape::Struct { fields, .. }) => {
+            &mut fields.iter_mut().find(|field| field.name == *name)?.shape
+        }
+        (Step::Variant(_, index), Shape::Enum { variants, .. }) => {
+            &mut variants.get_mut(*index)?.shape
+        }
+        _ => return None,
+    };
+
+    at(inner, rest)
+}
+
+struct Tracer<'s, 't> {
+    shape: &'s mut Shape,
+    path: Vec<Step>,
+    trace: &'t Trace,
+}
+
+impl<'s, 't> Tracer<'s, 't> {
+    fn nested<'n>(&self, shape: &'n mut Shape, step: Step) -> Tracer<'n, 't> {
+        let mut path = self.path.clone();
+        path.push(step);
+
+        Tracer {
+            shape,
+            path,
+            trace: self.trace,
+        }
+    }
+
+    fn can_nest(&self) -> bool {
+        self.path.len() < MAX_DEPTH
+    }
+}
+
+macro_rules! trace_scalar {
+    ($($method:ident => $shape:ident, $visit:ident($($value:expr)?);)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value>
+            where
+                V: Visitor<'de>,
+            {
+                *self.shape = Shape::$shape;
+                visitor.$visit($($value)?)
+            }
+        )*
+    };
+}
+
+impl<'s, 't, 'de> de::Deserializer<'de> for Tracer<'s, 't> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        *self.shape = Shape::Any;
+        visitor.visit_unit()
+    }
+
+    trace_scalar! {
+        deserialize_bool => Bool, visit_bool(false);
+        deserialize_i8 => Int, visit_i8(0);
+        deserialize_i16 => Int, visit_i16(0);
+        deserialize_i32 => Int, visit_i32(0);
+        deserialize_i64 => Int, visit_i64(0);
+        deserialize_i128 => Int, visit_i128(0);
+        deserialize_u8 => Int, visit_u8(0);
+        deserialize_u16 => Int, visit_u16(0);
+        deserialize_u32 => Int, visit_u32(0);
+        deserialize_u64 => Int, visit_u64(0);
+        deserialize_u128 => Int, visit_u128(0);
+        deserialize_f32 => Float, visit_f32(0.0);
+        deserialize_f64 => Float, visit_f64(0.0);
+        deserialize_char => Char, visit_char('\0');
+        deserialize_str => Str, visit_str("");
+        deserialize_string => Str, visit_str("");
+        deserialize_identifier => Str, visit_str("");
+        deserialize_bytes => Bytes, visit_bytes(&[]);
+        deserialize_byte_buf => Bytes, visit_bytes(&[]);
+        deserialize_unit => Unit, visit_unit();
+    }
+
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let mut inner = Shape::Any;
+        let value = if self.can_nest() {
+            visitor.visit_some(self.nested(&mut inner, Step::Some))?
+        } else {
+            visitor.visit_none::<Error>()?
+        };
+
+        *self.shape = Shape::Option(Box::new(inner));
+        Ok(value)
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let mut shapes = vec![Shape::Any];
+        let len = if self.can_nest() { 1 } else { 0 };
+        let value = visitor.visit_seq(SeqTracer {
+            shapes: &mut shapes[..len],
+            index: 0,
+            parent: &self,
+        })?;
+
+        *self.shape = Shape::Seq(Box::new(shapes.remove(0)));
+        Ok(value)
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let mut shapes = vec![Shape::Any; len];
+        let value = visitor.visit_seq(SeqTracer {
+            shapes: &mut shapes,
+            index: 0,
+            parent: &self,
+        })?;
+
+        *self.shape = Shape::Tuple(shapes);
+        Ok(value)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let mut key = Shape::Any;
+        let mut value = Shape::Any;
+        let result = visitor.visit_map(MapTracer {
+            key: &mut key,
+            value: &mut value,
+            remaining: if self.can_nest() { 1 } else { 0 },
+            parent: &self,
+        })?;
+
+        *self.shape = Shape::Map(Box::new(key), Box::new(value));
+        Ok(result)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let mut traced = fields
+            .iter()
+            .map(|&name| Field {
+                name,
+                shape: Shape::Any,
+            })
+            .collect::<Vec<_>>();
+
+        let value = visitor.visit_map(StructTracer {
+            fields: traced.iter_mut(),
+            current: None,
+            parent: &self,
+        })?;
+
+        *self.shape = Shape::Struct {
+            name,
+            fields: traced,
+        };
+        Ok(value)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let index = self.trace.choices.get(&self.path).copied().unwrap_or(0);
+        let variant = *variants.get(index).ok_or(Error::DeserializeNotSupported)?;
+        self.trace
+            .enums
+            .borrow_mut()
+            .push((self.path.clone(), name, variants.len()));
+
+        let mut traced = variants
+            .iter()
+            .map(|&name| Variant {
+                name,
+                shape: Shape::Any,
+            })
+            .collect::<Vec<_>>();
+
+        let value = visitor.visit_enum(EnumTracer {
+            variant,
+            content: self.nested(&mut traced[index].shape, Step::Variant(name, index)),
+        })?;
+
+        *self.shape = Shape::Enum {
+            name,
+            variants: traced,
+        };
+        Ok(value)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_any(visitor)
+    }
+}
+
+/// Yields one traced element per slot in `shapes`
+struct SeqTracer<'s, 'p, 't> {
+    shapes: &'s mut [Shape],
+    index: usize,
+    parent: &'p Tracer<'p, 't>,
+}
+
+impl<'s, 'p, 't, 'de> de::SeqAccess<'de> for SeqTracer<'s, 'p, 't> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let shapes = mem::take(&mut self.shapes);
+        match shapes.split_first_mut() {
+            Some((shape, rest)) => {
+                self.shapes = rest;
+                self.index += 1;
+                seed.deserialize(self.parent.nested(shape, Step::Elem(self.index - 1)))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.shapes.len())
+    }
+}
+
+/// Yields a single traced entry (or none once `MAX_DEPTH` is reached)
+struct MapTracer<'s, 'p, 't> {
+    key: &'s mut Shape,
+    value: &'s mut Shape,
+    remaining: usize,
+    parent: &'p Tracer<'p, 't>,
+}
+
+impl<'s, 'p, 't, 'de> de::MapAccess<'de> for MapTracer<'s, 'p, 't> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        self.remaining -= 1;
+        seed.deserialize(self.parent.nested(self.key, Step::Key))
+            .map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self.parent.nested(self.value, Step::Value))
+    }
+}
+
+/// Yields every field name of a struct with a traced value
+struct StructTracer<'s, 'p, 't> {
+    fields: std::slice::IterMut<'s, Field>,
+    current: Option<&'s mut Field>,
+    parent: &'p Tracer<'p, 't>,
+}
+
+impl<'s, 'p, 't, 'de> de::MapAccess<'de> for StructTracer<'s, 'p, 't> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match self.fields.next() {
+            Some(field) => {
+                let name: &'static str = field.name;
+                self.current = Some(field);
+                seed.deserialize(name.into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let field = self.current.take().ok_or(Error::EndOfStream)?;
+        let name = field.name;
+        seed.deserialize(self.parent.nested(&mut field.shape, Step::Field(name)))
+    }
+}
+
+/// Selects a variant of an enum and traces its content
+struct EnumTracer<'s, 't> {
+    variant: &'static str,
+    content: Tracer<'s, 't>,
+}
+
+impl<'s, 't, 'de> de::EnumAccess<'de> for EnumTracer<'s, 't> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self)>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let name: de::value::StrDeserializer<Error> = self.variant.into_deserializer();
+        let variant = seed.deserialize(name)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'s, 't, 'de> de::VariantAccess<'de> for EnumTracer<'s, 't> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        *self.content.shape = Shape::Unit;
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self.content)
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_tuple(self.content, len, visitor)
+    }
+
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_struct(self.content, self.variant, fields, visitor)
+    }
+}
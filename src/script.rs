@@ -0,0 +1,51 @@
+//! Typed results of Lua scripts
+//!
+//! Values returned by `EVAL`/`EVALSHA` go through Redis's Lua-to-RESP conversion, which loses some
+//! information: booleans become `Int(1)` and `Nil`, floats are truncated to integers, and tables
+//! are cut at their first `nil`. [`from_lua_value`] decodes with those rules in mind (see
+//! [`Deserializer::lua`](../decode/struct.Deserializer.html#method.lua)); return floats from
+//! scripts with `tostring` to keep them intact.
+//!
+//! ```no_run
+//! # fn f(con: &mut redis::Connection) -> redis::RedisResult<()> {
+//! use serde_redis::script;
+//!
+//! // Lua `false` is returned as `Nil`, which `bool` reads as `false`; only `nil` would cut the
+//! // table short
+//! let lock = redis::Script::new(
+//!     r"
+//!     local acquired = redis.call('SET', KEYS[1], ARGV[1], 'NX', 'PX', ARGV[2])
+//!     return {redis.call('GET', KEYS[1]), acquired ~= false}
+//!     ",
+//! );
+//!
+//! let (owner, acquired): (String, bool) =
+//!     script::invoke(lock.key("lock:jobs").arg("worker-1").arg(30_000), con)?;
+//! # Ok(())
+//! # }
+//! # fn main() {}
+//! ```
+use redis::{ConnectionLike, RedisResult, ScriptInvocation, Value};
+use serde::de::{Deserialize, DeserializeOwned};
+
+use crate::decode::{self, Deserializer};
+use crate::IntoCow;
+
+/// Build `T` from the result of a Lua script
+pub fn from_lua_value<'a, 'de, T, RV>(rv: RV) -> decode::Result<T>
+where
+    T: Deserialize<'de>,
+    RV: IntoCow<'a>,
+{
+    T::deserialize(Deserializer::lua(rv.into_cow()))
+}
+
+/// Run the script (loading it first if needed) and decode its result into `T`
+pub fn invoke<T, C>(invocation: &ScriptInvocation, con: &mut C) -> RedisResult<T>
+where
+    T: DeserializeOwned,
+    C: ConnectionLike,
+{
+    let value: Value = invocation.invoke(con)?;
+    Ok(from_lua_value(value)?)
+}
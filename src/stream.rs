@@ -0,0 +1,786 @@
+//! Typed replies for Redis streams
+//!
+//! `XRANGE` and `XREVRANGE` reply with a list of entries, each `[id, [field, value, ...]]`, which
+//! decodes as `Vec<StreamEntry<T>>`. `XREAD` and `XREADGROUP` wrap those lists per stream key,
+//! `[[key, [entry, ...]], ...]`, which decodes as `StreamReadReply<T>`. The fields of each entry
+//! decode into `T` the same way an `HGETALL` reply does.
+//!
+//! Consumer groups have their own reply shapes: `PendingSummary` and `PendingEntry` for the two
+//! forms of `XPENDING`, `ClaimReply<T>` for `XCLAIM` and `AutoClaimReply<T>` for `XAUTOCLAIM`.
+//!
+//! ```no_run
+//! # #[macro_use] extern crate serde_derive;
+//! # fn f(con: &mut redis::Connection) -> Result<(), Box<dyn std::error::Error>> {
+//! use serde_redis::stream::StreamReadReply;
+//!
+//! #[derive(Deserialize)]
+//! struct Click {
+//!     url: String,
+//! }
+//!
+//! let reply: redis::Value = redis::cmd("XREAD")
+//!     .arg("BLOCK").arg(1000)
+//!     .arg("STREAMS").arg("clicks").arg("$")
+//!     .query(con)?;
+//! let reply: StreamReadReply<Click> = serde_redis::from_redis_value(reply)?;
+//! for entry in reply.entries() {
+//!     println!("{} {}", entry.id, entry.fields.url);
+//! }
+//! # Ok(())
+//! # }
+//! # fn main() {}
+//! ```
+use redis::{ConnectionLike, RedisResult, RedisWrite, ToRedisArgs, Value};
+use serde::de::{self, Deserialize, SeqAccess, Visitor};
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use crate::decode::Error;
+use crate::encode::{self, to_hash_fields};
+use crate::from_redis_value;
+
+/// A stream entry ID, `<ms>-<seq>`
+///
+/// IDs order the way Redis orders them: by `ms`, then by `seq`. They decode from `Data` or
+/// `Status` replies and serialize, or pass as a command argument, in the canonical text form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct StreamId {
+    /// Milliseconds part, the Unix time the entry was added at unless the ID was given explicitly
+    pub ms: u64,
+    /// Sequence number among entries added in the same millisecond
+    pub seq: u64,
+}
+
+impl StreamId {
+    /// The smallest possible ID, `0-0`
+    pub const MIN: StreamId = StreamId { ms: 0, seq: 0 };
+    /// The largest possible ID
+    pub const MAX: StreamId = StreamId {
+        ms: u64::MAX,
+        seq: u64::MAX,
+    };
+
+    pub fn new(ms: u64, seq: u64) -> Self {
+        StreamId { ms, seq }
+    }
+
+    /// The smallest ID greater than this one, or `None` for `StreamId::MAX`
+    ///
+    /// Useful to continue an `XRANGE` scan after the last entry read.
+    pub fn next(self) -> Option<StreamId> {
+        match self.seq.checked_add(1) {
+            Some(seq) => Some(StreamId::new(self.ms, seq)),
+            None => self.ms.checked_add(1).map(|ms| StreamId::new(ms, 0)),
+        }
+    }
+
+    /// The largest ID smaller than this one, or `None` for `StreamId::MIN`
+    ///
+    /// Useful to continue an `XREVRANGE` scan after the last entry read.
+    pub fn prev(self) -> Option<StreamId> {
+        match self.seq.checked_sub(1) {
+            Some(seq) => Some(StreamId::new(self.ms, seq)),
+            None => self.ms.checked_sub(1).map(|ms| StreamId::new(ms, u64::MAX)),
+        }
+    }
+}
+
+impl fmt::Display for StreamId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.ms, self.seq)
+    }
+}
+
+impl FromStr for StreamId {
+    type Err = Error;
+
+    /// Parses `<ms>-<seq>`, or a bare `<ms>` which Redis reads as `<ms>-0`
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s.split_once('-') {
+            Some((ms, seq)) => Ok(StreamId::new(ms.parse()?, seq.parse()?)),
+            None => Ok(StreamId::new(s.parse()?, 0)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for StreamId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct IdVisitor;
+
+        impl<'de> Visitor<'de> for IdVisitor {
+            type Value = StreamId;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a stream ID")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<StreamId, E>
+            where
+                E: de::Error,
+            {
+                v.parse()
+                    .map_err(|_| E::invalid_value(de::Unexpected::Str(v), &self))
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<StreamId, E>
+            where
+                E: de::Error,
+            {
+                match std::str::from_utf8(v) {
+                    Ok(s) => self.visit_str(s),
+                    Err(_) => Err(E::invalid_value(de::Unexpected::Bytes(v), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_str(IdVisitor)
+    }
+}
+
+impl Serialize for StreamId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl ToRedisArgs for StreamId {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        out.write_arg_fmt(self)
+    }
+}
+
+/// A stream ID argument: either a concrete ID or one of the special tokens
+///
+/// Which tokens are meaningful depends on the command: `*` for `XADD`, `$` for `XREAD` and
+/// `XGROUP`, `>` for `XREADGROUP`, and `-`/`+` for `XRANGE`, `XREVRANGE` and `XPENDING`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StreamIdArg {
+    Id(StreamId),
+    /// `*`, let the server generate the ID
+    Auto,
+    /// `$`, the last ID in the stream
+    Last,
+    /// `>`, entries never delivered to any consumer of the group
+    Undelivered,
+    /// `-`, the smallest ID in the stream
+    Min,
+    /// `+`, the largest ID in the stream
+    Max,
+}
+
+impl From<StreamId> for StreamIdArg {
+    fn from(id: StreamId) -> Self {
+        StreamIdArg::Id(id)
+    }
+}
+
+impl fmt::Display for StreamIdArg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            StreamIdArg::Id(ref id) => id.fmt(f),
+            StreamIdArg::Auto => f.write_str("*"),
+            StreamIdArg::Last => f.write_str("$"),
+            StreamIdArg::Undelivered => f.write_str(">"),
+            StreamIdArg::Min => f.write_str("-"),
+            StreamIdArg::Max => f.write_str("+"),
+        }
+    }
+}
+
+impl FromStr for StreamIdArg {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Ok(match s {
+            "*" => StreamIdArg::Auto,
+            "$" => StreamIdArg::Last,
+            ">" => StreamIdArg::Undelivered,
+            "-" => StreamIdArg::Min,
+            "+" => StreamIdArg::Max,
+            _ => StreamIdArg::Id(s.parse()?),
+        })
+    }
+}
+
+impl Serialize for StreamIdArg {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl ToRedisArgs for StreamIdArg {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        out.write_arg_fmt(self)
+    }
+}
+
+/// One entry of a stream: its ID and its fields decoded into `T`
+///
+/// Commands that can name entries which were deleted meanwhile (such as `XCLAIM` before Redis 7)
+/// reply with `Nil` fields for them; use `StreamEntry<Option<T>>` to accept those, or
+/// `ClaimReply<T>` for `XCLAIM`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamEntry<T> {
+    pub id: StreamId,
+    pub fields: T,
+}
+
+impl<'de, T> Deserialize<'de> for StreamEntry<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct EntryVisitor<T>(PhantomData<T>);
+
+        impl<'de, T> Visitor<'de> for EntryVisitor<T>
+        where
+            T: Deserialize<'de>,
+        {
+            type Value = StreamEntry<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a stream entry [id, [field, value, ...]]")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let id = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let fields = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+
+                Ok(StreamEntry { id, fields })
+            }
+        }
+
+        deserializer.deserialize_tuple(2, EntryVisitor(PhantomData))
+    }
+}
+
+/// The entries read from one stream key by `XREAD` or `XREADGROUP`
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamKey<T> {
+    pub key: String,
+    pub entries: Vec<StreamEntry<T>>,
+}
+
+impl<'de, T> Deserialize<'de> for StreamKey<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let (key, entries) = Deserialize::deserialize(deserializer)?;
+        Ok(StreamKey { key, entries })
+    }
+}
+
+/// The reply to `XREAD` or `XREADGROUP`
+///
+/// A blocking read that times out replies with `Nil`, which decodes as a reply without keys.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamReadReply<T> {
+    pub keys: Vec<StreamKey<T>>,
+}
+
+impl<T> StreamReadReply<T> {
+    /// Whether no entries were read, for example because a blocking read timed out
+    pub fn is_empty(&self) -> bool {
+        self.keys.iter().all(|key| key.entries.is_empty())
+    }
+
+    /// All entries read, across every key, in reply order
+    pub fn entries(&self) -> impl Iterator<Item = &StreamEntry<T>> {
+        self.keys.iter().flat_map(|key| key.entries.iter())
+    }
+}
+
+impl<T> Default for StreamReadReply<T> {
+    fn default() -> Self {
+        StreamReadReply { keys: Vec::new() }
+    }
+}
+
+impl<'de, T> Deserialize<'de> for StreamReadReply<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let keys = Option::<Vec<StreamKey<T>>>::deserialize(deserializer)?;
+        Ok(StreamReadReply {
+            keys: keys.unwrap_or_default(),
+        })
+    }
+}
+
+/// The summary form of `XPENDING key group`
+///
+/// A group without pending entries replies with `0` and `Nil` for the rest, which decodes as
+/// `None` IDs and no consumers.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PendingSummary {
+    /// Number of entries delivered but not acknowledged
+    pub count: u64,
+    /// Smallest pending ID
+    pub min: Option<StreamId>,
+    /// Greatest pending ID
+    pub max: Option<StreamId>,
+    pub consumers: Vec<PendingConsumer>,
+}
+
+impl<'de> Deserialize<'de> for PendingSummary {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let (count, min, max, consumers): (_, _, _, Option<_>) =
+            Deserialize::deserialize(deserializer)?;
+        Ok(PendingSummary {
+            count,
+            min,
+            max,
+            consumers: consumers.unwrap_or_default(),
+        })
+    }
+}
+
+/// A consumer with pending entries, from the `XPENDING` summary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingConsumer {
+    pub name: String,
+    pub pending: u64,
+}
+
+impl<'de> Deserialize<'de> for PendingConsumer {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let (name, pending) = Deserialize::deserialize(deserializer)?;
+        Ok(PendingConsumer { name, pending })
+    }
+}
+
+/// One entry of the extended form, `XPENDING key group [IDLE ms] start end count [consumer]`
+///
+/// The reply is a list of these, so it decodes as `Vec<PendingEntry>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingEntry {
+    pub id: StreamId,
+    /// The consumer the entry was last delivered to
+    pub consumer: String,
+    /// Milliseconds since the entry was last delivered
+    pub idle_ms: u64,
+    /// Number of times the entry was delivered
+    pub deliveries: u64,
+}
+
+impl<'de> Deserialize<'de> for PendingEntry {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let (id, consumer, idle_ms, deliveries) = Deserialize::deserialize(deserializer)?;
+        Ok(PendingEntry {
+            id,
+            consumer,
+            idle_ms,
+            deliveries,
+        })
+    }
+}
+
+/// The reply to `XAUTOCLAIM key group consumer min-idle-time start`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoClaimReply<T> {
+    /// The `start` to pass to the next call; `0-0` once the whole pending list was scanned
+    pub next: StreamId,
+    pub entries: Vec<StreamEntry<T>>,
+    /// IDs that were pending but no longer exist in the stream
+    ///
+    /// Always empty before Redis 7, which does not report them.
+    pub deleted: Vec<StreamId>,
+}
+
+impl<T> AutoClaimReply<T> {
+    /// Whether the scan reached the end of the pending list
+    pub fn is_done(&self) -> bool {
+        self.next == StreamId::MIN
+    }
+}
+
+impl<'de, T> Deserialize<'de> for AutoClaimReply<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct AutoClaimVisitor<T>(PhantomData<T>);
+
+        impl<'de, T> Visitor<'de> for AutoClaimVisitor<T>
+        where
+            T: Deserialize<'de>,
+        {
+            type Value = AutoClaimReply<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an XAUTOCLAIM reply [cursor, [entry, ...], [id, ...]]")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let next = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let entries = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                let deleted = seq.next_element()?.unwrap_or_default();
+
+                Ok(AutoClaimReply {
+                    next,
+                    entries,
+                    deleted,
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(AutoClaimVisitor(PhantomData))
+    }
+}
+
+/// The reply to `XCLAIM key group consumer min-idle-time id... [JUSTID]`
+///
+/// Which form Redis replied with is told from the elements, so the same type decodes both. An
+/// empty reply decodes as `Entries` with nothing in it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClaimReply<T> {
+    /// The claimed entries with their fields
+    Entries {
+        entries: Vec<StreamEntry<T>>,
+        /// IDs that were pending but no longer exist in the stream
+        ///
+        /// Only Redis versions before 7 report these, as entries with `Nil` fields; later
+        /// versions leave them out of the reply.
+        deleted: Vec<StreamId>,
+    },
+    /// The claimed IDs, replied with `JUSTID`
+    Ids(Vec<StreamId>),
+}
+
+impl<T> Default for ClaimReply<T> {
+    fn default() -> Self {
+        ClaimReply::Entries {
+            entries: Vec::new(),
+            deleted: Vec::new(),
+        }
+    }
+}
+
+impl<'de, T> Deserialize<'de> for ClaimReply<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct ClaimVisitor<T>(PhantomData<T>);
+
+        impl<'de, T> Visitor<'de> for ClaimVisitor<T>
+        where
+            T: Deserialize<'de>,
+        {
+            type Value = ClaimReply<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an XCLAIM reply [entry, ...] or [id, ...]")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let mut reply = ClaimReply::default();
+                while let Some(claimed) = seq.next_element()? {
+                    match (&mut reply, claimed) {
+                        (ClaimReply::Ids(ids), Claimed::Id(id)) => ids.push(id),
+                        (ClaimReply::Entries { entries, deleted }, Claimed::Id(id))
+                            if entries.is_empty() && deleted.is_empty() =>
+                        {
+                            reply = ClaimReply::Ids(vec![id]);
+                        }
+                        (ClaimReply::Entries { entries, .. }, Claimed::Entry(id, Some(fields))) => {
+                            entries.push(StreamEntry { id, fields })
+                        }
+                        (ClaimReply::Entries { deleted, .. }, Claimed::Entry(id, None)) => {
+                            deleted.push(id)
+                        }
+                        _ => {
+                            return Err(de::Error::custom(
+                                "XCLAIM reply mixes entries and bare IDs",
+                            ))
+                        }
+                    }
+                }
+
+                Ok(reply)
+            }
+        }
+
+        deserializer.deserialize_seq(ClaimVisitor(PhantomData))
+    }
+}
+
+/// One element of an `XCLAIM` reply: a bare ID, or an ID with fields that are `Nil` once deleted
+enum Claimed<T> {
+    Id(StreamId),
+    Entry(StreamId, Option<T>),
+}
+
+impl<'de, T> Deserialize<'de> for Claimed<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct ClaimedVisitor<T>(PhantomData<T>);
+
+        impl<'de, T> Visitor<'de> for ClaimedVisitor<T>
+        where
+            T: Deserialize<'de>,
+        {
+            type Value = Claimed<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a stream ID or a stream entry [id, [field, value, ...]]")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                v.parse().map(Claimed::Id).map_err(E::custom)
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                match std::str::from_utf8(v) {
+                    Ok(v) => self.visit_str(v),
+                    Err(_) => Err(de::Error::invalid_value(de::Unexpected::Bytes(v), &self)),
+                }
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let id = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let fields = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+
+                Ok(Claimed::Entry(id, fields))
+            }
+        }
+
+        // The element's own type tells a bare ID from an entry
+        deserializer.deserialize_any(ClaimedVisitor(PhantomData))
+    }
+}
+
+/// How `XADD` trims the stream after adding an entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trim {
+    /// `MAXLEN [~] len`, keep at most `len` entries
+    MaxLen { approx: bool, len: usize },
+    /// `MINID [~] id`, drop entries with IDs below `id`
+    MinId { approx: bool, id: StreamId },
+}
+
+impl ToRedisArgs for Trim {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        let (strategy, approx) = match *self {
+            Trim::MaxLen { approx, .. } => ("MAXLEN", approx),
+            Trim::MinId { approx, .. } => ("MINID", approx),
+        };
+
+        out.write_arg(strategy.as_bytes());
+        if approx {
+            out.write_arg(b"~");
+        }
+
+        match *self {
+            Trim::MaxLen { len, .. } => len.write_redis_args(out),
+            Trim::MinId { id, .. } => id.write_redis_args(out),
+        }
+    }
+}
+
+/// Builds `XADD key [NOMKSTREAM] [MAXLEN|MINID [~] threshold] id field value ...`
+///
+/// The fields come from flattening `T` the same way `hset_struct` does, so an entry written here
+/// reads back as `StreamEntry<T>`. Fields that are `None` are left out.
+///
+/// ```no_run
+/// # #[macro_use] extern crate serde_derive;
+/// # fn f(con: &mut redis::Connection) -> redis::RedisResult<()> {
+/// use serde_redis::stream::XAdd;
+///
+/// #[derive(Serialize)]
+/// struct Click {
+///     url: String,
+/// }
+///
+/// let click = Click { url: "/".into() };
+/// let id = XAdd::new("clicks", &click).maxlen_approx(10_000).query(con)?;
+/// # Ok(())
+/// # }
+/// # fn main() {}
+/// ```
+#[derive(Debug)]
+pub struct XAdd<'a, K, T: ?Sized> {
+    key: K,
+    fields: &'a T,
+    id: StreamIdArg,
+    trim: Option<Trim>,
+    nomkstream: bool,
+}
+
+impl<'a, K, T> XAdd<'a, K, T>
+where
+    K: ToRedisArgs,
+    T: Serialize + ?Sized,
+{
+    /// Add `fields` to the stream at `key` with a server generated ID
+    pub fn new(key: K, fields: &'a T) -> Self {
+        XAdd {
+            key,
+            fields,
+            id: StreamIdArg::Auto,
+            trim: None,
+            nomkstream: false,
+        }
+    }
+
+    /// Use an explicit ID instead of letting the server generate one
+    pub fn id(mut self, id: StreamId) -> Self {
+        self.id = StreamIdArg::Id(id);
+        self
+    }
+
+    /// Keep at most `len` entries
+    pub fn maxlen(mut self, len: usize) -> Self {
+        self.trim = Some(Trim::MaxLen { approx: false, len });
+        self
+    }
+
+    /// Keep about `len` entries, letting the server trim in whole nodes
+    pub fn maxlen_approx(mut self, len: usize) -> Self {
+        self.trim = Some(Trim::MaxLen { approx: true, len });
+        self
+    }
+
+    /// Drop entries with IDs below `id`
+    pub fn minid(mut self, id: StreamId) -> Self {
+        self.trim = Some(Trim::MinId { approx: false, id });
+        self
+    }
+
+    /// Drop about the entries with IDs below `id`, letting the server trim in whole nodes
+    pub fn minid_approx(mut self, id: StreamId) -> Self {
+        self.trim = Some(Trim::MinId { approx: true, id });
+        self
+    }
+
+    /// Don't create the stream if it does not exist yet
+    pub fn nomkstream(mut self) -> Self {
+        self.nomkstream = true;
+        self
+    }
+
+    /// The command to send
+    pub fn cmd(&self) -> encode::Result<redis::Cmd> {
+        let mut cmd = redis::cmd("XADD");
+        cmd.arg(&self.key);
+
+        if self.nomkstream {
+            cmd.arg("NOMKSTREAM");
+        }
+
+        if let Some(trim) = self.trim {
+            cmd.arg(trim);
+        }
+
+        cmd.arg(self.id);
+
+        let mut empty = true;
+        for (field, value) in to_hash_fields(self.fields)? {
+            if let Value::Data(bytes) = value {
+                cmd.arg(field).arg(bytes);
+                empty = false;
+            }
+        }
+
+        if empty {
+            return Err(encode::Error::expected_hash(
+                "XADD needs at least one field",
+            ));
+        }
+
+        Ok(cmd)
+    }
+
+    /// Send the command and return the ID of the new entry
+    ///
+    /// Returns `None` when `nomkstream` is set and the stream does not exist.
+    pub fn query<C>(&self, con: &mut C) -> RedisResult<Option<StreamId>>
+    where
+        C: ConnectionLike,
+    {
+        let value: Value = self.cmd()?.query(con)?;
+        Ok(from_redis_value(value)?)
+    }
+}
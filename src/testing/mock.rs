@@ -0,0 +1,824 @@
+use redis::{ConnectionLike, RedisError, RedisResult, Value};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use crate::resp;
+
+/// A value stored under a key
+#[derive(Debug, Clone, PartialEq)]
+enum Entry {
+    String(Vec<u8>),
+    /// Fields in insertion order, as Redis keeps small hashes
+    Hash(Vec<(Vec<u8>, Vec<u8>)>),
+    List(VecDeque<Vec<u8>>),
+    Set(BTreeSet<Vec<u8>>),
+    SortedSet(BTreeMap<Vec<u8>, f64>),
+}
+
+impl Entry {
+    fn type_name(&self) -> &'static str {
+        match *self {
+            Entry::String(_) => "string",
+            Entry::Hash(_) => "hash",
+            Entry::List(_) => "list",
+            Entry::Set(_) => "set",
+            Entry::SortedSet(_) => "zset",
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match *self {
+            Entry::String(_) => false,
+            Entry::Hash(ref hash) => hash.is_empty(),
+            Entry::List(ref list) => list.is_empty(),
+            Entry::Set(ref set) => set.is_empty(),
+            Entry::SortedSet(ref zset) => zset.is_empty(),
+        }
+    }
+}
+
+/// An in-memory stand-in for a Redis connection
+///
+/// Commands are parsed from the bytes redis-rs sends and run against an in-memory keyspace of
+/// strings, hashes, lists, sets and sorted sets. Replies have the shapes a server sends: `Okay`
+/// for `SET`, `Nil` for missing keys, `Status` for `PING` and `TYPE`, and so on, so code under test
+/// and this crate's `Deserializer` see what they would against Redis.
+///
+/// Pipelines and `MULTI`/`EXEC` transactions work. `WATCH` aborts `EXEC` when a watched key's
+/// value changed in between. Expiry commands are accepted but keys never expire.
+///
+/// Supported commands: `PING`, `ECHO`, `DEL`, `EXISTS`, `TYPE`, `KEYS`, `FLUSHDB`, `FLUSHALL`,
+/// `EXPIRE`, `PEXPIRE`, `PERSIST`; `GET`, `SET` (with `NX`, `XX`, `EX`, `PX`), `SETNX`, `MGET`,
+/// `MSET`, `GETSET`, `APPEND`, `STRLEN`, `INCR`, `INCRBY`, `DECR`, `DECRBY`, `INCRBYFLOAT`;
+/// `HSET`, `HMSET`, `HSETNX`, `HGET`, `HMGET`, `HGETALL`, `HDEL`, `HEXISTS`, `HLEN`, `HKEYS`,
+/// `HVALS`, `HINCRBY`; `LPUSH`, `RPUSH`, `LPOP`, `RPOP`, `LLEN`, `LRANGE`, `LINDEX`; `SADD`,
+/// `SREM`, `SMEMBERS`, `SISMEMBER`, `SCARD`; `ZADD`, `ZREM`, `ZSCORE`, `ZINCRBY`, `ZCARD`,
+/// `ZRANGE` and `ZREVRANGE` (with `WITHSCORES`); `MULTI`, `EXEC`, `DISCARD`, `WATCH`, `UNWATCH`.
+/// Anything else is an `ERR unknown command` error.
+///
+/// ```
+/// use serde_redis::testing::MockConnection;
+///
+/// let mut con = MockConnection::new();
+/// redis::cmd("HSET").arg("user:1").arg("name").arg("ada").execute(&mut con);
+///
+/// let name: Option<String> = redis::cmd("HGET").arg("user:1").arg("name").query(&mut con).unwrap();
+/// assert_eq!(Some("ada".to_owned()), name);
+/// assert_eq!(2, con.commands().len());
+/// ```
+#[derive(Debug, Default)]
+pub struct MockConnection {
+    data: BTreeMap<Vec<u8>, Entry>,
+    db: i64,
+    /// Commands queued since `MULTI`
+    queued: Option<Vec<Vec<Vec<u8>>>>,
+    /// Watched keys and their values when `WATCH` ran
+    watched: Vec<(Vec<u8>, Option<Entry>)>,
+    commands: Vec<Vec<Vec<u8>>>,
+}
+
+type Args<'a> = &'a [&'a [u8]];
+
+/// The error redis-rs makes of the server's error reply `-line`
+fn error(line: &str) -> RedisError {
+    match redis::parse_redis_value(format!("-{}\r\n", line).as_bytes()) {
+        Err(err) => err,
+        Ok(_) => unreachable!("error replies parse as errors"),
+    }
+}
+
+fn arity_error(name: &str) -> RedisError {
+    error(&format!(
+        "ERR wrong number of arguments for '{}' command",
+        name.to_ascii_lowercase()
+    ))
+}
+
+fn wrong_type() -> RedisError {
+    error("WRONGTYPE Operation against a key holding the wrong kind of value")
+}
+
+fn syntax_error() -> RedisError {
+    error("ERR syntax error")
+}
+
+fn arity(name: &str, args: Args, min: usize) -> RedisResult<()> {
+    if args.len() < min {
+        return Err(arity_error(name));
+    }
+    Ok(())
+}
+
+fn parse_int(arg: &[u8]) -> RedisResult<i64> {
+    std::str::from_utf8(arg)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| error("ERR value is not an integer or out of range"))
+}
+
+fn parse_float(arg: &[u8]) -> RedisResult<f64> {
+    std::str::from_utf8(arg)
+        .ok()
+        .and_then(|s| s.parse::<f64>().ok())
+        .filter(|f| !f.is_nan())
+        .ok_or_else(|| error("ERR value is not a valid float"))
+}
+
+/// Format a double the way Redis replies with it
+fn format_float(f: f64) -> Vec<u8> {
+    if f == f64::INFINITY {
+        b"inf".to_vec()
+    } else if f == f64::NEG_INFINITY {
+        b"-inf".to_vec()
+    } else {
+        f.to_string().into_bytes()
+    }
+}
+
+fn data(bytes: &[u8]) -> Value {
+    Value::Data(bytes.to_vec())
+}
+
+fn int(i: usize) -> Value {
+    Value::Int(i as i64)
+}
+
+fn bulk<'a, I>(items: I) -> Value
+where
+    I: IntoIterator<Item = &'a Vec<u8>>,
+{
+    Value::Bulk(items.into_iter().map(|item| data(item)).collect())
+}
+
+/// Resolve `start` and `stop` indexes, which may count from the end, into a range of `len`
+fn range(start: i64, stop: i64, len: usize) -> Option<(usize, usize)> {
+    let len = len as i64;
+    let start = if start < 0 {
+        (len + start).max(0)
+    } else {
+        start
+    };
+    let stop = if stop < 0 {
+        len + stop
+    } else {
+        stop.min(len - 1)
+    };
+
+    if start > stop || start >= len {
+        None
+    } else {
+        Some((start as usize, stop as usize))
+    }
+}
+
+/// Match `name` against a `KEYS` pattern with `*`, `?` and `\` escapes
+fn glob(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|i| glob(rest, &name[i..])),
+        Some((b'?', rest)) => !name.is_empty() && glob(rest, &name[1..]),
+        Some((b'\\', rest)) if !rest.is_empty() => {
+            name.first() == Some(&rest[0]) && glob(&rest[1..], &name[1..])
+        }
+        Some((c, rest)) => name.first() == Some(c) && glob(rest, &name[1..]),
+    }
+}
+
+macro_rules! typed_entry {
+    ($get:ident, $get_mut:ident, $variant:ident, $ty:ty) => {
+        fn $get(&self, key: &[u8]) -> RedisResult<Option<&$ty>> {
+            match self.data.get(key) {
+                None => Ok(None),
+                Some(Entry::$variant(ref value)) => Ok(Some(value)),
+                Some(_) => Err(wrong_type()),
+            }
+        }
+
+        /// The value at `key`, created empty if the key does not exist
+        fn $get_mut(&mut self, key: &[u8]) -> RedisResult<&mut $ty> {
+            let entry = self
+                .data
+                .entry(key.to_vec())
+                .or_insert_with(|| Entry::$variant(Default::default()));
+            match *entry {
+                Entry::$variant(ref mut value) => Ok(value),
+                _ => Err(wrong_type()),
+            }
+        }
+    };
+}
+
+impl MockConnection {
+    pub fn new() -> Self {
+        MockConnection::default()
+    }
+
+    /// Every command received so far, as its arguments
+    pub fn commands(&self) -> &[Vec<Vec<u8>>] {
+        &self.commands
+    }
+
+    typed_entry!(hash, hash_mut, Hash, Vec<(Vec<u8>, Vec<u8>)>);
+    typed_entry!(list, list_mut, List, VecDeque<Vec<u8>>);
+    typed_entry!(set, set_mut, Set, BTreeSet<Vec<u8>>);
+    typed_entry!(zset, zset_mut, SortedSet, BTreeMap<Vec<u8>, f64>);
+
+    fn string(&self, key: &[u8]) -> RedisResult<Option<&Vec<u8>>> {
+        match self.data.get(key) {
+            None => Ok(None),
+            Some(Entry::String(ref value)) => Ok(Some(value)),
+            Some(_) => Err(wrong_type()),
+        }
+    }
+
+    /// Redis deletes aggregates when their last element is removed
+    fn remove_if_empty(&mut self, key: &[u8]) {
+        if self.data.get(key).is_some_and(Entry::is_empty) {
+            self.data.remove(key);
+        }
+    }
+
+    /// Members of a sorted set ordered by score, then member
+    fn sorted(&self, key: &[u8]) -> RedisResult<Vec<(&Vec<u8>, f64)>> {
+        let mut members: Vec<_> = match self.zset(key)? {
+            Some(zset) => zset
+                .iter()
+                .map(|(member, score)| (member, *score))
+                .collect(),
+            None => Vec::new(),
+        };
+        members.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(b.0)));
+        Ok(members)
+    }
+
+    fn incr_by(&mut self, key: &[u8], by: i64) -> RedisResult<Value> {
+        let current = match self.string(key)? {
+            Some(value) => parse_int(value)?,
+            None => 0,
+        };
+        let next = current
+            .checked_add(by)
+            .ok_or_else(|| error("ERR increment or decrement would overflow"))?;
+
+        self.data
+            .insert(key.to_vec(), Entry::String(next.to_string().into_bytes()));
+        Ok(Value::Int(next))
+    }
+
+    fn pop(&mut self, key: &[u8], count: Option<&[u8]>, front: bool) -> RedisResult<Value> {
+        let count = count.map(parse_int).transpose()?;
+        let list = match self.data.get(key) {
+            None => return Ok(Value::Nil),
+            Some(Entry::List(_)) => self.list_mut(key)?,
+            Some(_) => return Err(wrong_type()),
+        };
+
+        let mut popped = Vec::new();
+        for _ in 0..count.unwrap_or(1).max(0) {
+            let item = if front {
+                list.pop_front()
+            } else {
+                list.pop_back()
+            };
+            match item {
+                Some(item) => popped.push(item),
+                None => break,
+            }
+        }
+        self.remove_if_empty(key);
+
+        Ok(match count {
+            Some(_) => bulk(&popped),
+            None => popped.first().map_or(Value::Nil, |item| data(item)),
+        })
+    }
+
+    fn zrange(&self, args: Args, reverse: bool) -> RedisResult<Value> {
+        let with_scores = match args.get(3) {
+            None => false,
+            Some(arg) if arg.eq_ignore_ascii_case(b"WITHSCORES") => true,
+            Some(_) => return Err(syntax_error()),
+        };
+
+        let mut members = self.sorted(args[0])?;
+        if reverse {
+            members.reverse();
+        }
+
+        let (start, stop) = match range(parse_int(args[1])?, parse_int(args[2])?, members.len()) {
+            Some(range) => range,
+            None => return Ok(Value::Bulk(vec![])),
+        };
+
+        let mut reply = Vec::new();
+        for &(member, score) in &members[start..=stop] {
+            reply.push(data(member));
+            if with_scores {
+                reply.push(Value::Data(format_float(score)));
+            }
+        }
+        Ok(Value::Bulk(reply))
+    }
+
+    fn set_string(&mut self, args: Args) -> RedisResult<Value> {
+        let (mut nx, mut xx, mut get) = (false, false, false);
+        let mut options = args[2..].iter();
+        while let Some(option) = options.next() {
+            match option.to_ascii_uppercase().as_slice() {
+                b"NX" => nx = true,
+                b"XX" => xx = true,
+                b"GET" => get = true,
+                b"KEEPTTL" => {}
+                b"EX" | b"PX" | b"EXAT" | b"PXAT" => {
+                    parse_int(options.next().ok_or_else(syntax_error)?)?;
+                }
+                _ => return Err(syntax_error()),
+            }
+        }
+        if nx && xx {
+            return Err(syntax_error());
+        }
+
+        let old = match self.data.get(args[0]) {
+            None => None,
+            Some(Entry::String(ref value)) => Some(value.clone()),
+            Some(_) if get => return Err(wrong_type()),
+            Some(_) => None,
+        };
+        let exists = self.data.contains_key(args[0]);
+
+        let written = !(nx && exists || xx && !exists);
+        if written {
+            self.data
+                .insert(args[0].to_vec(), Entry::String(args[1].to_vec()));
+        }
+
+        Ok(match (get, written) {
+            (true, _) => old.map_or(Value::Nil, Value::Data),
+            (false, true) => Value::Okay,
+            (false, false) => Value::Nil,
+        })
+    }
+
+    /// Run one command, or queue it inside `MULTI`
+    fn execute(&mut self, command: &[&[u8]]) -> RedisResult<Value> {
+        let (name, args) = command
+            .split_first()
+            .ok_or_else(|| error("ERR empty command"))?;
+        let name = String::from_utf8_lossy(name).to_ascii_uppercase();
+
+        if let Some(ref mut queued) = self.queued {
+            match name.as_str() {
+                "EXEC" | "DISCARD" | "MULTI" | "WATCH" => {}
+                _ => {
+                    queued.push(command.iter().map(|arg| arg.to_vec()).collect());
+                    return Ok(Value::Status("QUEUED".to_owned()));
+                }
+            }
+        }
+
+        self.run(&name, args)
+    }
+
+    fn run(&mut self, name: &str, args: Args) -> RedisResult<Value> {
+        let min = match name {
+            "PING" | "FLUSHDB" | "FLUSHALL" | "MULTI" | "EXEC" | "DISCARD" | "UNWATCH" => 0,
+            "ECHO" | "DEL" | "EXISTS" | "TYPE" | "KEYS" | "PERSIST" | "GET" | "MGET" | "STRLEN"
+            | "INCR" | "DECR" | "HGETALL" | "HLEN" | "HKEYS" | "HVALS" | "LPOP" | "RPOP"
+            | "LLEN" | "SMEMBERS" | "SCARD" | "ZCARD" | "WATCH" => 1,
+            "EXPIRE" | "PEXPIRE" | "SET" | "SETNX" | "MSET" | "GETSET" | "APPEND" | "INCRBY"
+            | "DECRBY" | "INCRBYFLOAT" | "HGET" | "HMGET" | "HDEL" | "HEXISTS" | "LPUSH"
+            | "RPUSH" | "LINDEX" | "SADD" | "SREM" | "SISMEMBER" | "ZREM" | "ZSCORE" => 2,
+            "HSET" | "HMSET" | "HSETNX" | "HINCRBY" | "LRANGE" | "ZADD" | "ZINCRBY" | "ZRANGE"
+            | "ZREVRANGE" => 3,
+            _ => {
+                return Err(error(&format!(
+                    "ERR unknown command '{}'",
+                    name.to_ascii_lowercase()
+                )))
+            }
+        };
+        arity(name, args, min)?;
+
+        let key = args.first().copied().unwrap_or_default();
+        match name {
+            // Keys and connection
+            "PING" => Ok(match args.first() {
+                Some(msg) => data(msg),
+                None => Value::Status("PONG".to_owned()),
+            }),
+            "ECHO" => Ok(data(key)),
+            "DEL" => Ok(int(args
+                .iter()
+                .filter(|key| self.data.remove(**key).is_some())
+                .count())),
+            "EXISTS" => Ok(int(args
+                .iter()
+                .filter(|key| self.data.contains_key(**key))
+                .count())),
+            "TYPE" => Ok(Value::Status(
+                self.data
+                    .get(key)
+                    .map_or("none", Entry::type_name)
+                    .to_owned(),
+            )),
+            "KEYS" => Ok(bulk(self.data.keys().filter(|name| glob(key, name)))),
+            "FLUSHDB" | "FLUSHALL" => {
+                self.data.clear();
+                Ok(Value::Okay)
+            }
+            "EXPIRE" | "PEXPIRE" => {
+                parse_int(args[1])?;
+                Ok(int(self.data.contains_key(key) as usize))
+            }
+            "PERSIST" => Ok(Value::Int(0)),
+
+            // Strings
+            "GET" => Ok(self.string(key)?.map_or(Value::Nil, |value| data(value))),
+            "SET" => self.set_string(args),
+            "SETNX" => {
+                if self.data.contains_key(key) {
+                    return Ok(Value::Int(0));
+                }
+                self.data
+                    .insert(key.to_vec(), Entry::String(args[1].to_vec()));
+                Ok(Value::Int(1))
+            }
+            "MGET" => Ok(Value::Bulk(
+                args.iter()
+                    .map(|key| match self.data.get(*key) {
+                        Some(Entry::String(ref value)) => data(value),
+                        _ => Value::Nil,
+                    })
+                    .collect(),
+            )),
+            "MSET" => {
+                let pairs = args.chunks_exact(2);
+                if !pairs.remainder().is_empty() {
+                    return Err(arity_error(name));
+                }
+                for pair in pairs {
+                    self.data
+                        .insert(pair[0].to_vec(), Entry::String(pair[1].to_vec()));
+                }
+                Ok(Value::Okay)
+            }
+            "GETSET" => {
+                let old = self.string(key)?.map_or(Value::Nil, |value| data(value));
+                self.data
+                    .insert(key.to_vec(), Entry::String(args[1].to_vec()));
+                Ok(old)
+            }
+            "APPEND" => {
+                let mut value = self.string(key)?.cloned().unwrap_or_default();
+                value.extend_from_slice(args[1]);
+                let len = value.len();
+                self.data.insert(key.to_vec(), Entry::String(value));
+                Ok(int(len))
+            }
+            "STRLEN" => Ok(int(self.string(key)?.map_or(0, Vec::len))),
+            "INCR" => self.incr_by(key, 1),
+            "DECR" => self.incr_by(key, -1),
+            "INCRBY" => self.incr_by(key, parse_int(args[1])?),
+            "DECRBY" => {
+                let by = parse_int(args[1])?
+                    .checked_neg()
+                    .ok_or_else(|| error("ERR decrement would overflow"))?;
+                self.incr_by(key, by)
+            }
+            "INCRBYFLOAT" => {
+                let current = match self.string(key)? {
+                    Some(value) => parse_float(value)?,
+                    None => 0.0,
+                };
+                let next = current + parse_float(args[1])?;
+                if !next.is_finite() {
+                    return Err(error("ERR increment would produce NaN or Infinity"));
+                }
+                let next = format_float(next);
+                self.data.insert(key.to_vec(), Entry::String(next.clone()));
+                Ok(Value::Data(next))
+            }
+
+            // Hashes
+            "HSET" | "HMSET" => {
+                let pairs = args[1..].chunks_exact(2);
+                if !pairs.remainder().is_empty() {
+                    return Err(arity_error(name));
+                }
+                let hash = self.hash_mut(key)?;
+                let mut added = 0;
+                for pair in pairs {
+                    match hash.iter_mut().find(|(field, _)| field == pair[0]) {
+                        Some((_, value)) => *value = pair[1].to_vec(),
+                        None => {
+                            hash.push((pair[0].to_vec(), pair[1].to_vec()));
+                            added += 1;
+                        }
+                    }
+                }
+                Ok(if name == "HSET" {
+                    int(added)
+                } else {
+                    Value::Okay
+                })
+            }
+            "HSETNX" => {
+                let hash = self.hash_mut(key)?;
+                if hash.iter().any(|(field, _)| field == args[1]) {
+                    return Ok(Value::Int(0));
+                }
+                hash.push((args[1].to_vec(), args[2].to_vec()));
+                Ok(Value::Int(1))
+            }
+            "HGET" => Ok(self
+                .hash(key)?
+                .and_then(|hash| hash.iter().find(|(field, _)| field == args[1]))
+                .map_or(Value::Nil, |(_, value)| data(value))),
+            "HMGET" => {
+                let hash = self.hash(key)?;
+                Ok(Value::Bulk(
+                    args[1..]
+                        .iter()
+                        .map(|name| {
+                            hash.and_then(|hash| hash.iter().find(|(field, _)| field == name))
+                                .map_or(Value::Nil, |(_, value)| data(value))
+                        })
+                        .collect(),
+                ))
+            }
+            "HGETALL" => Ok(bulk(
+                self.hash(key)?
+                    .into_iter()
+                    .flatten()
+                    .flat_map(|(field, value)| vec![field, value]),
+            )),
+            "HDEL" => {
+                if self.hash(key)?.is_none() {
+                    return Ok(Value::Int(0));
+                }
+                let hash = self.hash_mut(key)?;
+                let before = hash.len();
+                hash.retain(|(field, _)| !args[1..].contains(&field.as_slice()));
+                let removed = before - hash.len();
+                self.remove_if_empty(key);
+                Ok(int(removed))
+            }
+            "HEXISTS" => Ok(int(self
+                .hash(key)?
+                .is_some_and(|hash| hash.iter().any(|(field, _)| field == args[1]))
+                as usize)),
+            "HLEN" => Ok(int(self.hash(key)?.map_or(0, Vec::len))),
+            "HKEYS" => Ok(bulk(self.hash(key)?.into_iter().flatten().map(|(f, _)| f))),
+            "HVALS" => Ok(bulk(self.hash(key)?.into_iter().flatten().map(|(_, v)| v))),
+            "HINCRBY" => {
+                let by = parse_int(args[2])?;
+                let hash = self.hash_mut(key)?;
+                let position = hash.iter().position(|(field, _)| field == args[1]);
+                let current = match position {
+                    Some(i) => parse_int(&hash[i].1)
+                        .map_err(|_| error("ERR hash value is not an integer"))?,
+                    None => 0,
+                };
+                let next = current
+                    .checked_add(by)
+                    .ok_or_else(|| error("ERR increment or decrement would overflow"))?;
+                let value = next.to_string().into_bytes();
+                match position {
+                    Some(i) => hash[i].1 = value,
+                    None => hash.push((args[1].to_vec(), value)),
+                }
+                Ok(Value::Int(next))
+            }
+
+            // Lists
+            "LPUSH" | "RPUSH" => {
+                let list = self.list_mut(key)?;
+                for item in &args[1..] {
+                    if name == "LPUSH" {
+                        list.push_front(item.to_vec());
+                    } else {
+                        list.push_back(item.to_vec());
+                    }
+                }
+                Ok(int(list.len()))
+            }
+            "LPOP" => self.pop(key, args.get(1).copied(), true),
+            "RPOP" => self.pop(key, args.get(1).copied(), false),
+            "LLEN" => Ok(int(self.list(key)?.map_or(0, VecDeque::len))),
+            "LRANGE" => {
+                let list = match self.list(key)? {
+                    Some(list) => list,
+                    None => return Ok(Value::Bulk(vec![])),
+                };
+                Ok(
+                    match range(parse_int(args[1])?, parse_int(args[2])?, list.len()) {
+                        Some((start, stop)) => bulk(list.range(start..=stop)),
+                        None => Value::Bulk(vec![]),
+                    },
+                )
+            }
+            "LINDEX" => {
+                let index = parse_int(args[1])?;
+                let list = self.list(key)?;
+                let len = list.map_or(0, VecDeque::len) as i64;
+                let index = if index < 0 { len + index } else { index };
+                Ok(list
+                    .filter(|_| index >= 0)
+                    .and_then(|list| list.get(index as usize))
+                    .map_or(Value::Nil, |item| data(item)))
+            }
+
+            // Sets
+            "SADD" => {
+                let set = self.set_mut(key)?;
+                Ok(int(args[1..]
+                    .iter()
+                    .filter(|member| set.insert(member.to_vec()))
+                    .count()))
+            }
+            "SREM" => {
+                if self.set(key)?.is_none() {
+                    return Ok(Value::Int(0));
+                }
+                let set = self.set_mut(key)?;
+                let removed = args[1..]
+                    .iter()
+                    .filter(|member| set.remove(**member))
+                    .count();
+                self.remove_if_empty(key);
+                Ok(int(removed))
+            }
+            "SMEMBERS" => Ok(bulk(self.set(key)?.into_iter().flatten())),
+            "SISMEMBER" => Ok(int(
+                self.set(key)?.is_some_and(|set| set.contains(args[1])) as usize
+            )),
+            "SCARD" => Ok(int(self.set(key)?.map_or(0, BTreeSet::len))),
+
+            // Sorted sets
+            "ZADD" => {
+                let pairs = args[1..].chunks_exact(2);
+                if !pairs.remainder().is_empty() {
+                    return Err(syntax_error());
+                }
+                let pairs = pairs
+                    .map(|pair| Ok((parse_float(pair[0])?, pair[1])))
+                    .collect::<RedisResult<Vec<_>>>()?;
+
+                let zset = self.zset_mut(key)?;
+                Ok(int(pairs
+                    .into_iter()
+                    .filter(|&(score, member)| zset.insert(member.to_vec(), score).is_none())
+                    .count()))
+            }
+            "ZINCRBY" => {
+                let by = parse_float(args[1])?;
+                let current = self.zset(key)?.and_then(|zset| zset.get(args[2]));
+                let score = current.copied().unwrap_or(0.0) + by;
+                if score.is_nan() {
+                    return Err(error("ERR resulting score is not a number (NaN)"));
+                }
+                self.zset_mut(key)?.insert(args[2].to_vec(), score);
+                Ok(Value::Data(format_float(score)))
+            }
+            "ZREM" => {
+                if self.zset(key)?.is_none() {
+                    return Ok(Value::Int(0));
+                }
+                let zset = self.zset_mut(key)?;
+                let removed = args[1..]
+                    .iter()
+                    .filter(|member| zset.remove(**member).is_some())
+                    .count();
+                self.remove_if_empty(key);
+                Ok(int(removed))
+            }
+            "ZSCORE" => Ok(self
+                .zset(key)?
+                .and_then(|zset| zset.get(args[1]))
+                .map_or(Value::Nil, |score| Value::Data(format_float(*score)))),
+            "ZCARD" => Ok(int(self.zset(key)?.map_or(0, BTreeMap::len))),
+            "ZRANGE" => self.zrange(args, false),
+            "ZREVRANGE" => self.zrange(args, true),
+
+            // Transactions
+            "MULTI" => {
+                if self.queued.is_some() {
+                    return Err(error("ERR MULTI calls can not be nested"));
+                }
+                self.queued = Some(Vec::new());
+                Ok(Value::Okay)
+            }
+            "DISCARD" => {
+                self.queued
+                    .take()
+                    .ok_or_else(|| error("ERR DISCARD without MULTI"))?;
+                self.watched.clear();
+                Ok(Value::Okay)
+            }
+            "EXEC" => {
+                let queued = self
+                    .queued
+                    .take()
+                    .ok_or_else(|| error("ERR EXEC without MULTI"))?;
+                let watched = std::mem::take(&mut self.watched);
+                if watched
+                    .iter()
+                    .any(|(key, value)| self.data.get(key) != value.as_ref())
+                {
+                    return Ok(Value::Nil);
+                }
+
+                // Every queued command runs even if one fails, as on a server
+                let mut replies = Vec::with_capacity(queued.len());
+                let mut first_error = None;
+                for command in &queued {
+                    let command: Vec<&[u8]> = command.iter().map(Vec::as_slice).collect();
+                    let (name, args) = command.split_first().unwrap();
+                    let name = String::from_utf8_lossy(name).to_ascii_uppercase();
+                    match self.run(&name, args) {
+                        Ok(reply) => replies.push(reply),
+                        Err(err) => {
+                            first_error.get_or_insert(err);
+                        }
+                    }
+                }
+
+                match first_error {
+                    Some(err) => Err(err),
+                    None => Ok(Value::Bulk(replies)),
+                }
+            }
+            "WATCH" => {
+                if self.queued.is_some() {
+                    return Err(error("ERR WATCH inside MULTI is not allowed"));
+                }
+                for key in args {
+                    let value = self.data.get(*key).cloned();
+                    self.watched.push((key.to_vec(), value));
+                }
+                Ok(Value::Okay)
+            }
+            "UNWATCH" => {
+                self.watched.clear();
+                Ok(Value::Okay)
+            }
+            _ => unreachable!("arity table covers every command"),
+        }
+    }
+
+    /// Parse and run each command in `packed`, in order
+    ///
+    /// Like a server, a failing command does not stop the ones after it; the first error is
+    /// returned once all have run.
+    fn run_packed(&mut self, packed: &[u8]) -> RedisResult<Vec<Value>> {
+        let mut de = resp::Deserializer::from_slice(packed);
+        let mut replies = Vec::new();
+        let mut first_error = None;
+
+        while de.end().is_err() {
+            let command: Vec<&[u8]> = serde::Deserialize::deserialize(&mut de)?;
+            self.commands
+                .push(command.iter().map(|arg| arg.to_vec()).collect());
+            match self.execute(&command) {
+                Ok(reply) => replies.push(reply),
+                Err(err) => {
+                    first_error.get_or_insert(err);
+                }
+            }
+        }
+
+        match first_error {
+            Some(err) => Err(err),
+            None => Ok(replies),
+        }
+    }
+}
+
+impl ConnectionLike for MockConnection {
+    fn req_packed_command(&mut self, cmd: &[u8]) -> RedisResult<Value> {
+        let mut replies = self.run_packed(cmd)?;
+        Ok(replies.pop().unwrap_or(Value::Nil))
+    }
+
+    fn req_packed_commands(
+        &mut self,
+        cmd: &[u8],
+        offset: usize,
+        count: usize,
+    ) -> RedisResult<Vec<Value>> {
+        let replies = self.run_packed(cmd)?;
+        Ok(replies.into_iter().skip(offset).take(count).collect())
+    }
+
+    fn get_db(&self) -> i64 {
+        self.db
+    }
+
+    fn check_connection(&mut self) -> bool {
+        true
+    }
+
+    fn is_open(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,22 @@
+//! Helpers for testing code that talks to Redis, without a server
+//!
+//! [`MockConnection`] implements `redis::ConnectionLike` over an in-memory keyspace, so commands,
+//! pipelines and transactions built with redis-rs run offline and reply with the same
+//! `redis::Value` shapes a server would. Values written with `RedisSerdeExt` or this crate's
+//! `Serializer` can be read back with `Deserializer` to check they round-trip.
+//!
+//! [`assert_roundtrip`] checks a value directly: it is written in every layout this crate
+//! supports and read back from each. With the `proptest` or `quickcheck` feature,
+//! `proptest_roundtrip` and `quickcheck_roundtrip` do the same for generated values, which finds
+//! the edge cases (`u64::MAX`, empty strings, non-ASCII text, NaN) a handful of fixtures miss.
+//!
+//! Enabled by the `testing` feature.
+mod mock;
+mod roundtrip;
+
+pub use self::mock::MockConnection;
+#[cfg(feature = "proptest")]
+pub use self::roundtrip::proptest_roundtrip;
+#[cfg(feature = "quickcheck")]
+pub use self::roundtrip::quickcheck_roundtrip;
+pub use self::roundtrip::{assert_roundtrip, check_roundtrip, Error, Layout, Result};
@@ -0,0 +1,226 @@
+use redis::Value;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::error;
+use std::fmt;
+
+use crate::{encode, from_redis_value, resp, to_redis_value};
+
+/// A way values are written to and read back from Redis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// `to_redis_value`, read back with `from_redis_value`
+    Value,
+    /// `to_hash_fields` stored with `HSET` and read with `HGETALL`; `None` fields are left out
+    Hash,
+    /// `resp::to_vec`, read back with `resp::from_slice`
+    Resp2,
+    /// `resp::Serializer::resp3`, read back with `resp::from_slice`
+    Resp3,
+}
+
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Layout::Value => "redis::Value",
+            Layout::Hash => "hash fields",
+            Layout::Resp2 => "RESP2",
+            Layout::Resp3 => "RESP3",
+        })
+    }
+}
+
+type BoxError = Box<dyn error::Error + Send + Sync>;
+
+/// A value that did not survive a round trip
+#[derive(Debug)]
+pub enum Error {
+    /// The value could not be written in the layout
+    Encode(Layout, BoxError),
+    /// What was written could not be read back
+    Decode(Layout, BoxError),
+    /// What was read back differs from the value; holds both, formatted with `Debug`
+    Mismatch {
+        layout: Layout,
+        expected: String,
+        actual: String,
+    },
+}
+
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Encode(_, ref err) | Error::Decode(_, ref err) => Some(&**err),
+            Error::Mismatch { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Encode(layout, ref err) => write!(f, "could not write as {}: {}", layout, err),
+            Error::Decode(layout, ref err) => write!(f, "could not read back {}: {}", layout, err),
+            Error::Mismatch {
+                layout,
+                ref expected,
+                ref actual,
+            } => write!(
+                f,
+                "value changed in a round trip through {}\n  expected: {}\n    actual: {}",
+                layout, expected, actual
+            ),
+        }
+    }
+}
+
+/// Check that `value` reads back unchanged from every layout it can be written in
+///
+/// The [`Hash`](Layout::Hash) layout only applies to structs and maps of scalars, and is skipped
+/// for other values. Values that are not equal to themselves, such as NaN floats, are compared by
+/// what they serialize to instead.
+///
+/// Returns the first failure. The error implements `std::error::Error`, so inside a `proptest!`
+/// block `check_roundtrip(&value)?` fails the case, and a quickcheck property may return it.
+pub fn check_roundtrip<T>(value: &T) -> Result<()>
+where
+    T: Serialize + DeserializeOwned + PartialEq + fmt::Debug,
+{
+    let encoded = to_redis_value(value).map_err(|err| Error::Encode(Layout::Value, err.into()))?;
+    let decoded: T =
+        from_redis_value(&encoded).map_err(|err| Error::Decode(Layout::Value, err.into()))?;
+    compare(Layout::Value, value, &decoded)?;
+
+    match encode::to_hash_fields(value) {
+        Ok(fields) => {
+            let mut hash = Vec::with_capacity(fields.len() * 2);
+            for (field, value) in fields {
+                if let Value::Data(_) = value {
+                    hash.push(Value::Data(field));
+                    hash.push(value);
+                }
+            }
+
+            let decoded: T = from_redis_value(Value::Bulk(hash))
+                .map_err(|err| Error::Decode(Layout::Hash, err.into()))?;
+            compare(Layout::Hash, value, &decoded)?;
+        }
+        Err(encode::Error::ExpectedHash(_)) => {}
+        Err(err) => return Err(Error::Encode(Layout::Hash, err.into())),
+    }
+
+    let bytes = resp::to_vec(value).map_err(|err| Error::Encode(Layout::Resp2, err.into()))?;
+    let decoded: T =
+        resp::from_slice(&bytes).map_err(|err| Error::Decode(Layout::Resp2, err.into()))?;
+    compare(Layout::Resp2, value, &decoded)?;
+
+    let mut serializer = resp::Serializer::resp3(Vec::new());
+    value
+        .serialize(&mut serializer)
+        .map_err(|err| Error::Encode(Layout::Resp3, err.into()))?;
+    let decoded: T = resp::from_slice(&serializer.into_inner())
+        .map_err(|err| Error::Decode(Layout::Resp3, err.into()))?;
+    compare(Layout::Resp3, value, &decoded)
+}
+
+fn compare<T>(layout: Layout, expected: &T, actual: &T) -> Result<()>
+where
+    T: Serialize + PartialEq + fmt::Debug,
+{
+    #[allow(clippy::eq_op)]
+    let reflexive = expected == expected;
+    let equal = if reflexive {
+        expected == actual
+    } else {
+        to_redis_value(expected).ok() == to_redis_value(actual).ok()
+    };
+
+    if equal {
+        Ok(())
+    } else {
+        Err(Error::Mismatch {
+            layout,
+            expected: format!("{:?}", expected),
+            actual: format!("{:?}", actual),
+        })
+    }
+}
+
+/// Assert that `value` reads back unchanged from every layout, as [`check_roundtrip`] does
+///
+/// ```
+/// # #[macro_use] extern crate serde_derive;
+/// use serde_redis::testing::assert_roundtrip;
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct User {
+///     name: String,
+///     visits: u64,
+///     email: Option<String>,
+/// }
+///
+/// assert_roundtrip(&User { name: "émile".to_owned(), visits: u64::MAX, email: None });
+/// # fn main() {}
+/// ```
+///
+/// # Panics
+///
+/// Panics with the layout and both values if the value does not round-trip.
+#[track_caller]
+pub fn assert_roundtrip<T>(value: &T)
+where
+    T: Serialize + DeserializeOwned + PartialEq + fmt::Debug,
+{
+    if let Err(err) = check_roundtrip(value) {
+        panic!("{}", err);
+    }
+}
+
+/// Check [`check_roundtrip`] for values generated by proptest's `any::<T>()`
+///
+/// Failures are shrunk to a minimal value before panicking. Use `check_roundtrip` inside a
+/// `proptest!` block for custom strategies or settings.
+#[cfg(feature = "proptest")]
+#[track_caller]
+pub fn proptest_roundtrip<T>()
+where
+    T: proptest::arbitrary::Arbitrary + Serialize + DeserializeOwned + PartialEq + fmt::Debug,
+{
+    use proptest::test_runner::{Config, TestRunner};
+
+    let config = Config {
+        failure_persistence: None,
+        ..Config::default()
+    };
+    let mut runner = TestRunner::new(config);
+    let result = runner.run(&proptest::arbitrary::any::<T>(), |value| {
+        check_roundtrip(&value)?;
+        Ok(())
+    });
+
+    if let Err(err) = result {
+        panic!("{}", err);
+    }
+}
+
+/// Check [`check_roundtrip`] for values generated by quickcheck's `Arbitrary`
+///
+/// # Panics
+///
+/// Panics with the shrunk value if it does not round-trip.
+#[cfg(feature = "quickcheck")]
+pub fn quickcheck_roundtrip<T>()
+where
+    T: quickcheck::Arbitrary + Serialize + DeserializeOwned + PartialEq + fmt::Debug,
+{
+    fn property<T>(value: T) -> Result<()>
+    where
+        T: Serialize + DeserializeOwned + PartialEq + fmt::Debug,
+    {
+        check_roundtrip(&value)
+    }
+
+    quickcheck::QuickCheck::new().quickcheck(property::<T> as fn(T) -> Result<()>);
+}
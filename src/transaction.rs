@@ -0,0 +1,170 @@
+//! Typed `MULTI`/`EXEC` replies
+//!
+//! `EXEC` replies with one value per queued command, or with `Nil` when a key passed to `WATCH`
+//! changed before the transaction ran. [`exec`] decodes the first case like a pipeline (see
+//! [`pipeline::from_replies`](../pipeline/fn.from_replies.html)) and reports the second as
+//! [`Exec::Aborted`] rather than as an error. [`update`] builds an optimistic read-modify-write
+//! loop for structs stored as hashes on top of it.
+//!
+//! ```no_run
+//! # fn f(con: &mut redis::Connection) -> redis::RedisResult<()> {
+//! use serde_redis::transaction::{self, Exec};
+//!
+//! redis::cmd("WATCH").arg("balance").query::<()>(con)?;
+//! let balance: i64 = redis::cmd("GET").arg("balance").query(con)?;
+//!
+//! let mut pipe = redis::pipe();
+//! pipe.atomic().set("balance", balance - 10).ignore().incr("spent", 10);
+//!
+//! match transaction::exec::<(i64,), _>(&pipe, con)? {
+//!     Exec::Committed((spent,)) => println!("spent {}", spent),
+//!     Exec::Aborted => println!("balance changed, try again"),
+//! }
+//! # Ok(())
+//! # }
+//! # fn main() {}
+//! ```
+use redis::{ConnectionLike, Pipeline, RedisResult, ToRedisArgs, Value};
+use serde::de::{self, Deserialize, DeserializeOwned};
+use serde::Serialize;
+
+use crate::diff::diff;
+use crate::pipeline::{self, SlotError};
+use crate::{from_redis_value, IntoCow};
+
+/// The outcome of `EXEC`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Exec<T> {
+    /// The transaction ran; holds the decoded replies
+    Committed(T),
+    /// A watched key changed and no command was run
+    Aborted,
+}
+
+impl<T> Exec<T> {
+    pub fn is_aborted(&self) -> bool {
+        match *self {
+            Exec::Committed(_) => false,
+            Exec::Aborted => true,
+        }
+    }
+
+    /// The replies, or `None` if the transaction was aborted
+    pub fn committed(self) -> Option<T> {
+        match self {
+            Exec::Committed(value) => Some(value),
+            Exec::Aborted => None,
+        }
+    }
+}
+
+/// Decodes `Nil` as `Aborted` and anything else as `Committed`
+///
+/// The replies are decoded as a single value, so structs are read as field/value pairs. Use
+/// `from_replies` to match struct fields to commands by position instead.
+impl<'de, T> Deserialize<'de> for Exec<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        Ok(match Option::<T>::deserialize(deserializer)? {
+            Some(value) => Exec::Committed(value),
+            None => Exec::Aborted,
+        })
+    }
+}
+
+/// Run the transaction in `pipe` and decode the `EXEC` reply into `T`
+///
+/// `pipe` should be marked with `atomic()`.
+pub fn exec<T, C>(pipe: &Pipeline, con: &mut C) -> RedisResult<Exec<T>>
+where
+    T: DeserializeOwned,
+    C: ConnectionLike,
+{
+    let value: Value = pipe.query(con)?;
+    Ok(from_replies(pipe, value)?)
+}
+
+/// Decode the `EXEC` reply of `pipe` into `T`, one slot per command
+pub fn from_replies<'a, 'de, T, RV>(pipe: &Pipeline, rv: RV) -> Result<Exec<T>, SlotError>
+where
+    T: Deserialize<'de>,
+    RV: IntoCow<'a>,
+{
+    let rv = rv.into_cow();
+    if let Value::Nil = *rv {
+        return Ok(Exec::Aborted);
+    }
+
+    pipeline::from_replies(pipe, rv).map(Exec::Committed)
+}
+
+/// Optimistically update the struct stored as a hash at `key`
+///
+/// Watches `key`, reads it with `HGETALL`, and passes it to `f`. The fields that differ in the
+/// returned value are written (and those that became `None` deleted) in a transaction, which is
+/// retried from the read if `key` changed meanwhile. Gives up with `Aborted` after `attempts`
+/// tries; otherwise returns the value that was stored.
+///
+/// If anything fails after `WATCH`, such as decoding the hash, `key` is unwatched before the
+/// error is returned, so later commands on `con` are not affected by it.
+pub fn update<T, K, C, F>(con: &mut C, key: K, attempts: usize, mut f: F) -> RedisResult<Exec<T>>
+where
+    T: Serialize + DeserializeOwned,
+    K: ToRedisArgs,
+    C: ConnectionLike,
+    F: FnMut(&T) -> T,
+{
+    for _ in 0..attempts {
+        redis::cmd("WATCH").arg(&key).query::<()>(con)?;
+
+        match try_update(con, &key, &mut f) {
+            Ok(Some(new)) => return Ok(Exec::Committed(new)),
+            Ok(None) => {}
+            Err(err) => {
+                // The error is more useful than a failure to unwatch, which a broken connection
+                // would cause as well
+                let _ = redis::cmd("UNWATCH").query::<()>(con);
+                return Err(err);
+            }
+        }
+    }
+
+    Ok(Exec::Aborted)
+}
+
+/// One read-modify-write of `update` on a watched `key`; `None` if the transaction was aborted
+fn try_update<T, K, C, F>(con: &mut C, key: &K, f: &mut F) -> RedisResult<Option<T>>
+where
+    T: Serialize + DeserializeOwned,
+    K: ToRedisArgs,
+    C: ConnectionLike,
+    F: FnMut(&T) -> T,
+{
+    let value: Value = redis::cmd("HGETALL").arg(key).query(con)?;
+    let old: T = from_redis_value(value)?;
+    let new = f(&old);
+
+    let changes = diff(&old, &new)?;
+    if changes.is_empty() {
+        redis::cmd("UNWATCH").query::<()>(con)?;
+        return Ok(Some(new));
+    }
+
+    let mut pipe = redis::pipe();
+    pipe.atomic();
+    for cmd in changes.cmds(key) {
+        pipe.add_command(cmd).ignore();
+    }
+
+    let value: Value = pipe.query(con)?;
+    if value == Value::Nil {
+        return Ok(None);
+    }
+
+    Ok(Some(new))
+}
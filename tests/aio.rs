@@ -0,0 +1,99 @@
+#![cfg(feature = "aio")]
+
+#[macro_use]
+extern crate serde_derive;
+
+mod common;
+
+use common::Replay;
+use futures_executor::block_on;
+use redis::Value;
+use serde_redis::AsyncRedisSerdeExt;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct User {
+    name: String,
+    visits: u32,
+    email: Option<String>,
+}
+
+#[test]
+fn hget_struct_decodes_hgetall() {
+    let mut con = Replay::new(vec![Value::Bulk(vec![
+        Value::Data(b"name".to_vec()),
+        Value::Data(b"ada".to_vec()),
+        Value::Data(b"visits".to_vec()),
+        Value::Data(b"3".to_vec()),
+    ])]);
+
+    let user: User = block_on(con.hget_struct("user:1")).unwrap();
+
+    let expected = User {
+        name: "ada".to_owned(),
+        visits: 3,
+        email: None,
+    };
+    assert_eq!(expected, user);
+    assert_eq!(
+        redis::cmd("HGETALL").arg("user:1").get_packed_command(),
+        con.sent[0]
+    );
+}
+
+#[test]
+fn hset_struct_skips_none_fields() {
+    let mut con = Replay::new(vec![Value::Int(2)]);
+    let user = User {
+        name: "ada".to_owned(),
+        visits: 3,
+        email: None,
+    };
+
+    block_on(con.hset_struct("user:1", &user)).unwrap();
+
+    let expected = redis::cmd("HSET")
+        .arg("user:1")
+        .arg("name")
+        .arg("ada")
+        .arg("visits")
+        .arg("3")
+        .get_packed_command();
+    assert_eq!(vec![expected], con.sent);
+}
+
+#[test]
+fn get_as_and_mget_as_handle_missing_keys() {
+    let mut con = Replay::new(vec![
+        Value::Nil,
+        Value::Bulk(vec![Value::Data(b"1".to_vec()), Value::Nil]),
+    ]);
+
+    let missing: Option<u64> = block_on(con.get_as("counter")).unwrap();
+    let mget: Vec<Option<u8>> = block_on(con.mget_as(&["x", "y"])).unwrap();
+
+    assert_eq!(None, missing);
+    assert_eq!(vec![Some(1), None], mget);
+}
+
+#[test]
+fn lrange_and_smembers_decode_elements() {
+    let mut con = Replay::new(vec![
+        Value::Bulk(vec![Value::Data(b"a".to_vec())]),
+        Value::Bulk(vec![Value::Data(b"7".to_vec())]),
+    ]);
+
+    let lrange: Vec<String> = block_on(con.lrange_as("list", 0, -1)).unwrap();
+    let smembers: Vec<u16> = block_on(con.smembers_as("set")).unwrap();
+
+    assert_eq!(vec!["a".to_owned()], lrange);
+    assert_eq!(vec![7], smembers);
+}
+
+#[test]
+fn decode_errors_become_type_errors() {
+    let mut con = Replay::new(vec![Value::Data(b"nope".to_vec())]);
+
+    let err = block_on(con.get_as::<_, u64>("counter")).unwrap_err();
+
+    assert_eq!(redis::ErrorKind::TypeError, err.kind());
+}
@@ -0,0 +1,93 @@
+use std::collections::VecDeque;
+
+use redis::{RedisResult, Value};
+
+/// Replies to each command with the next queued values and records what was sent
+#[derive(Default)]
+pub struct Replay {
+    pub sent: Vec<Vec<u8>>,
+    replies: VecDeque<Value>,
+}
+
+impl Replay {
+    pub fn new(replies: Vec<Value>) -> Self {
+        Replay {
+            sent: Vec::new(),
+            replies: replies.into(),
+        }
+    }
+
+    /// The name of each command sent, or of the first command of each pipeline
+    #[allow(dead_code)]
+    pub fn sent_commands(&self) -> Vec<String> {
+        self.sent
+            .iter()
+            .map(|cmd| {
+                let cmd = String::from_utf8_lossy(cmd);
+                cmd.split("\r\n").nth(2).unwrap_or_default().to_owned()
+            })
+            .collect()
+    }
+
+    fn next_reply(&mut self) -> Value {
+        self.replies.pop_front().expect("no reply queued")
+    }
+
+    /// Replies to a pipeline, of which only `count` after `offset` are returned
+    fn next_replies(&mut self, offset: usize, count: usize) -> Vec<Value> {
+        self.replies.drain(..offset + count).skip(offset).collect()
+    }
+}
+
+impl redis::ConnectionLike for Replay {
+    fn req_packed_command(&mut self, cmd: &[u8]) -> RedisResult<Value> {
+        self.sent.push(cmd.to_vec());
+        Ok(self.next_reply())
+    }
+
+    fn req_packed_commands(
+        &mut self,
+        cmd: &[u8],
+        offset: usize,
+        count: usize,
+    ) -> RedisResult<Vec<Value>> {
+        self.sent.push(cmd.to_vec());
+        Ok(self.next_replies(offset, count))
+    }
+
+    fn get_db(&self) -> i64 {
+        0
+    }
+
+    fn check_connection(&mut self) -> bool {
+        true
+    }
+
+    fn is_open(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(feature = "aio")]
+impl redis::aio::ConnectionLike for Replay {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a redis::Cmd) -> redis::RedisFuture<'a, Value> {
+        self.sent.push(cmd.get_packed_command());
+        let reply = self.next_reply();
+        Box::pin(async move { Ok(reply) })
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a redis::Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> redis::RedisFuture<'a, Vec<Value>> {
+        self.sent.push(cmd.get_packed_pipeline());
+        let replies = self.next_replies(offset, count);
+        Box::pin(async move { Ok(replies) })
+    }
+
+    fn get_db(&self) -> i64 {
+        0
+    }
+}
@@ -6,16 +6,17 @@ extern crate serde;
 extern crate serde_bytes;
 extern crate serde_redis;
 
+use std::borrow::Cow;
 use std::collections::HashMap;
 
 use serde::Deserialize;
-use serde_redis::Deserializer;
+use serde_redis::{decode, redis_value, Deserializer, Pairs, StreamDeserializer};
 
 use redis::Value;
 
 #[test]
 fn deserialize_unit_struct_string() {
-    let v = Value::Data(b"hello".to_vec());
+    let v = redis_value!("hello");
 
     #[derive(Deserialize, Debug, PartialEq)]
     struct Unit(String);
@@ -30,7 +31,7 @@ fn deserialize_unit_struct_string() {
 #[test]
 fn deserialize_unit_struct_u8_redis_int() {
     let num = 5u8;
-    let v = Value::Int(num as i64);
+    let v = redis_value!(num);
 
     #[derive(Deserialize, Debug, PartialEq)]
     struct IntUnit(u8);
@@ -44,16 +45,7 @@ fn deserialize_unit_struct_u8_redis_int() {
 
 #[test]
 fn deserialize_bool() {
-    let v = vec![
-        Value::Data(b"0".to_vec()),
-        Value::Data(b"false".to_vec()),
-        Value::Data(b"False".to_vec()),
-        Value::Data(b"1".to_vec()),
-        Value::Data(b"true".to_vec()),
-        Value::Data(b"True".to_vec()),
-    ];
-
-    let data = Value::Bulk(v);
+    let data = redis_value!(["0", "false", "False", "1", "true", "True"]);
 
     let de = Deserializer::new(&data);
     let actual: Vec<bool> = Deserialize::deserialize(de).unwrap();
@@ -64,9 +56,7 @@ fn deserialize_bool() {
 
 #[test]
 fn deserialize_tuple() {
-    let v = vec![Value::Int(5), Value::Data(b"hello".to_vec())];
-
-    let data = Value::Bulk(v);
+    let data = redis_value!([5, "hello"]);
 
     let de = Deserializer::new(&data);
     let actual: (u8, String) = Deserialize::deserialize(de).unwrap();
@@ -77,12 +67,7 @@ fn deserialize_tuple() {
 
 #[test]
 fn deserialize_struct() {
-    let v = vec![
-        Value::Data(b"a".to_vec()),
-        Value::Data(b"apple".to_vec()),
-        Value::Data(b"b".to_vec()),
-        Value::Data(b"banana".to_vec()),
-    ];
+    let v = redis_value!({ "a": "apple", "b": "banana" });
 
     #[derive(Debug, Deserialize, PartialEq)]
     struct Simple {
@@ -90,9 +75,7 @@ fn deserialize_struct() {
         b: String,
     }
 
-    let data = Value::Bulk(v);
-
-    let de = Deserializer::new(&data);
+    let de = Deserializer::new(&v);
     let actual: Simple = Deserialize::deserialize(de).unwrap();
 
     let expected = Simple {
@@ -105,20 +88,13 @@ fn deserialize_struct() {
 
 #[test]
 fn deserialize_hash_map_strings() {
-    let v = vec![
-        Value::Data(b"a".to_vec()),
-        Value::Data(b"apple".to_vec()),
-        Value::Data(b"b".to_vec()),
-        Value::Data(b"banana".to_vec()),
-    ];
+    let v = redis_value!({ "a": "apple", "b": "banana" });
 
     let mut expected = HashMap::new();
     expected.insert("a".to_string(), "apple".to_string());
     expected.insert("b".to_string(), "banana".to_string());
 
-    let data = Value::Bulk(v);
-
-    let de = Deserializer::new(&data);
+    let de = Deserializer::new(&v);
     let actual: HashMap<String, String> = Deserialize::deserialize(de).unwrap();
 
     assert_eq!(expected, actual);
@@ -126,7 +102,7 @@ fn deserialize_hash_map_strings() {
 
 #[test]
 fn deserialize_float() {
-    let v = Value::Data(b"3.14159".to_vec());
+    let v = redis_value!("3.14159");
 
     let expected = "3.14159".parse::<f32>().unwrap();
 
@@ -138,20 +114,13 @@ fn deserialize_float() {
 
 #[test]
 fn deserialize_hash_map_string_u8() {
-    let v = vec![
-        Value::Data(b"a".to_vec()),
-        Value::Data(b"1".to_vec()),
-        Value::Data(b"b".to_vec()),
-        Value::Data(b"2".to_vec()),
-    ];
+    let v = redis_value!({ "a": "1", "b": "2" });
 
     let mut expected = HashMap::new();
     expected.insert("a".to_string(), 1);
     expected.insert("b".to_string(), 2);
 
-    let data = Value::Bulk(v);
-
-    let de = Deserializer::new(&data);
+    let de = Deserializer::new(&v);
     let actual: HashMap<String, u8> = Deserialize::deserialize(de).unwrap();
 
     assert_eq!(expected, actual);
@@ -159,12 +128,7 @@ fn deserialize_hash_map_string_u8() {
 
 #[test]
 fn deserialize_struct_out_of_order() {
-    let v = vec![
-        Value::Data(b"b".to_vec()),
-        Value::Data(b"banana".to_vec()),
-        Value::Data(b"a".to_vec()),
-        Value::Data(b"apple".to_vec()),
-    ];
+    let v = redis_value!({ "b": "banana", "a": "apple" });
 
     #[derive(Debug, Deserialize, PartialEq)]
     struct Simple {
@@ -172,9 +136,7 @@ fn deserialize_struct_out_of_order() {
         b: String,
     }
 
-    let data = Value::Bulk(v);
-
-    let de = Deserializer::new(&data);
+    let de = Deserializer::new(&v);
     let actual: Simple = Deserialize::deserialize(de).unwrap();
 
     let expected = Simple {
@@ -187,14 +149,7 @@ fn deserialize_struct_out_of_order() {
 
 #[test]
 fn deserialize_struct_extra_keys() {
-    let v = vec![
-        Value::Data(b"c".to_vec()),
-        Value::Data(b"cranberry".to_vec()),
-        Value::Data(b"b".to_vec()),
-        Value::Data(b"banana".to_vec()),
-        Value::Data(b"a".to_vec()),
-        Value::Data(b"apple".to_vec()),
-    ];
+    let v = redis_value!({ "c": "cranberry", "b": "banana", "a": "apple" });
 
     #[derive(Debug, Deserialize, PartialEq)]
     struct Simple {
@@ -202,9 +157,7 @@ fn deserialize_struct_extra_keys() {
         b: String,
     }
 
-    let data = Value::Bulk(v);
-
-    let de = Deserializer::new(&data);
+    let de = Deserializer::new(&v);
     let actual: Simple = Deserialize::deserialize(de).unwrap();
 
     let expected = Simple {
@@ -217,7 +170,7 @@ fn deserialize_struct_extra_keys() {
 
 #[test]
 fn deserialize_enum() {
-    let v = Value::Data(b"Orange".to_vec());
+    let v = redis_value!("Orange");
 
     #[derive(Debug, Deserialize, PartialEq)]
     enum Fruit {
@@ -233,7 +186,7 @@ fn deserialize_enum() {
 
 #[test]
 fn deserialize_option() {
-    let de = Deserializer::new(&Value::Nil);
+    let de = Deserializer::new(&redis_value!(nil));
     let actual: Option<u8> = Deserialize::deserialize(de).unwrap();
 
     assert_eq!(None, actual);
@@ -241,14 +194,7 @@ fn deserialize_option() {
 
 #[test]
 fn deserialize_complex_struct() {
-    let v = vec![
-        Value::Data(b"num".to_vec()),
-        Value::Data(b"10".to_vec()),
-        Value::Data(b"opt".to_vec()),
-        Value::Data(b"yes".to_vec()),
-        Value::Data(b"s".to_vec()),
-        Value::Data(b"yarn".to_vec()),
-    ];
+    let v = redis_value!({ "num": "10", "opt": "yes", "s": "yarn" });
 
     #[derive(Debug, Deserialize, PartialEq)]
     struct Complex {
@@ -265,9 +211,7 @@ fn deserialize_complex_struct() {
         s: "yarn".to_owned(),
     };
 
-    let data = Value::Bulk(v);
-
-    let de = Deserializer::new(&data);
+    let de = Deserializer::new(&v);
     let actual: Complex = Deserialize::deserialize(de).unwrap();
 
     assert_eq!(expected, actual);
@@ -275,15 +219,9 @@ fn deserialize_complex_struct() {
 
 #[test]
 fn deserialize_vec_of_strings() {
-    let v = vec![
-        Value::Data(b"first".to_vec()),
-        Value::Data(b"second".to_vec()),
-        Value::Data(b"third".to_vec()),
-    ];
-
-    let data = Value::Bulk(v);
+    let v = redis_value!(["first", "second", "third"]);
 
-    let de = Deserializer::new(&data);
+    let de = Deserializer::new(&v);
     let actual: Vec<String> = Deserialize::deserialize(de).unwrap();
 
     let expected = vec![
@@ -296,18 +234,12 @@ fn deserialize_vec_of_strings() {
 
 #[test]
 fn deserialize_vec_of_newtype() {
-    let v = vec![
-        Value::Data(b"first".to_vec()),
-        Value::Data(b"second".to_vec()),
-        Value::Data(b"third".to_vec()),
-    ];
+    let v = redis_value!(["first", "second", "third"]);
 
     #[derive(Debug, PartialEq, Deserialize)]
     struct Rank(String);
 
-    let data = Value::Bulk(v);
-
-    let de = Deserializer::new(&data);
+    let de = Deserializer::new(&v);
     let actual: Vec<Rank> = Deserialize::deserialize(de).unwrap();
 
     let expected = vec![
@@ -325,19 +257,9 @@ fn deserialize_vec_of_newtype() {
 /// not handle this.
 #[test]
 fn deserialize_pipelined_hmap() {
-    let values = Value::Bulk(vec![
-        Value::Bulk(vec![
-            Value::Data(b"a".to_vec()),
-            Value::Data(b"apple".to_vec()),
-            Value::Data(b"b".to_vec()),
-            Value::Data(b"banana".to_vec()),
-        ]),
-        Value::Bulk(vec![
-            Value::Data(b"a".to_vec()),
-            Value::Data(b"art".to_vec()),
-            Value::Data(b"b".to_vec()),
-            Value::Data(b"bold".to_vec()),
-        ]),
+    let values = redis_value!([
+        { "a": "apple", "b": "banana" },
+        { "a": "art", "b": "bold" },
     ]);
 
     #[derive(Debug, Deserialize, PartialEq)]
@@ -365,12 +287,7 @@ fn deserialize_pipelined_hmap() {
 
 #[test]
 fn deserialize_pipelined_single_hmap() {
-    let values = Value::Bulk(vec![Value::Bulk(vec![
-        Value::Data(b"a".to_vec()),
-        Value::Data(b"apple".to_vec()),
-        Value::Data(b"b".to_vec()),
-        Value::Data(b"banana".to_vec()),
-    ])]);
+    let values = redis_value!([{ "a": "apple", "b": "banana" }]);
 
     #[derive(Debug, Deserialize, PartialEq)]
     struct Simple {
@@ -391,12 +308,7 @@ fn deserialize_pipelined_single_hmap() {
 
 #[test]
 fn deserialize_struct_with_newtype_field() {
-    let v = vec![
-        Value::Data(b"b".to_vec()),
-        Value::Data(b"banana".to_vec()),
-        Value::Data(b"a".to_vec()),
-        Value::Data(b"apple".to_vec()),
-    ];
+    let v = redis_value!({ "b": "banana", "a": "apple" });
 
     #[derive(Debug, Deserialize, PartialEq)]
     struct Fruit(String);
@@ -407,9 +319,7 @@ fn deserialize_struct_with_newtype_field() {
         b: Fruit,
     }
 
-    let data = Value::Bulk(v);
-
-    let de = Deserializer::new(&data);
+    let de = Deserializer::new(&v);
     let actual: Simple = Deserialize::deserialize(de).unwrap();
 
     let expected = Simple {
@@ -422,7 +332,7 @@ fn deserialize_struct_with_newtype_field() {
 
 #[test]
 fn deserialize_byte_buf() {
-    let data = Value::Data(b"0000".to_vec());
+    let data = redis_value!(b"0000");
     let de = Deserializer::new(&data);
     let actual: serde_bytes::ByteBuf = Deserialize::deserialize(de).unwrap();
 
@@ -435,12 +345,7 @@ fn deserialize_pipelined_single_hmap_newtype_fields() {
     #[derive(Debug, Deserialize, PartialEq)]
     struct Fruit(String);
 
-    let values = Value::Bulk(vec![Value::Bulk(vec![
-        Value::Data(b"a".to_vec()),
-        Value::Data(b"apple".to_vec()),
-        Value::Data(b"b".to_vec()),
-        Value::Data(b"banana".to_vec()),
-    ])]);
+    let values = redis_value!([{ "a": "apple", "b": "banana" }]);
 
     #[derive(Debug, Deserialize, PartialEq)]
     struct Simple {
@@ -470,21 +375,17 @@ type MapMapList = ::std::collections::HashMap<String, Details>;
 
 #[test]
 fn deserialize_nested_map_map_list() {
-    let value = Value::Bulk(vec![
-        Value::Data(b"key".to_vec()),
-        Value::Bulk(vec![
-            Value::Data(b"count".to_vec()),
-            Value::Data(b"4".to_vec()),
-            Value::Data(b"time".to_vec()),
-            Value::Data(b"1473359995".to_vec()),
-            Value::Data(b"ids".to_vec()),
-            Value::Bulk(vec![
-                Value::Data(b"00000000-0000-0000-0000-000000000000".to_vec()),
-                Value::Data(b"00000000-0000-0000-0000-000000000001".to_vec()),
-                Value::Data(b"00000000-0000-0000-0000-000000000002".to_vec()),
-            ]),
-        ]),
-    ]);
+    let value = redis_value!({
+        "key": {
+            "count": "4",
+            "time": "1473359995",
+            "ids": [
+                "00000000-0000-0000-0000-000000000000",
+                "00000000-0000-0000-0000-000000000001",
+                "00000000-0000-0000-0000-000000000002",
+            ],
+        },
+    });
 
     let de = Deserializer::new(&value);
     let map: MapMapList = Deserialize::deserialize(de).unwrap();
@@ -505,8 +406,244 @@ fn deserialize_nested_map_map_list() {
 #[test]
 #[should_panic]
 fn deserialize_nested_item() {
-    let value = Value::Bulk(vec![Value::Bulk(vec![Value::Data(b"hi".to_vec())])]);
+    let value = redis_value!([["hi"]]);
 
     let de = Deserializer::new(&value);
     let _hellos: Vec<String> = Deserialize::deserialize(de).unwrap();
 }
+
+#[test]
+fn deserialize_zrange_withscores_pairs() {
+    let value = redis_value!(["carol", "-inf", "alice", "1.5", "bob", "inf"]);
+
+    let de = Deserializer::new(&value);
+    let actual: Pairs<String, f64> = Deserialize::deserialize(de).unwrap();
+
+    let expected = vec![
+        ("carol".to_owned(), f64::NEG_INFINITY),
+        ("alice".to_owned(), 1.5),
+        ("bob".to_owned(), f64::INFINITY),
+    ];
+    assert_eq!(expected, actual.into_inner());
+}
+
+#[test]
+fn deserialize_pairs_with_newtype_members() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct UserId(u64);
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Leaderboard {
+        #[serde(deserialize_with = "serde_redis::pairs::deserialize")]
+        top: Vec<(UserId, f64)>,
+    }
+
+    let value = redis_value!({ "top": ["42", "+inf", "7", "3"] });
+
+    let de = Deserializer::new(&value);
+    let actual: Leaderboard = Deserialize::deserialize(de).unwrap();
+
+    let expected = Leaderboard {
+        top: vec![(UserId(42), f64::INFINITY), (UserId(7), 3.0)],
+    };
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn deserialize_pairs_with_struct_members() {
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Member {
+        name: String,
+        level: u8,
+    }
+
+    let value =
+        redis_value!([{ "name": "ada", "level": 3 }, "2.5", { "name": "bob", "level": 1 }, "-inf"]);
+
+    let de = Deserializer::new(&value);
+    let actual: Pairs<Member, f64> = Deserialize::deserialize(de).unwrap();
+
+    let expected = vec![
+        (
+            Member {
+                name: "ada".to_owned(),
+                level: 3,
+            },
+            2.5,
+        ),
+        (
+            Member {
+                name: "bob".to_owned(),
+                level: 1,
+            },
+            f64::NEG_INFINITY,
+        ),
+    ];
+    assert_eq!(expected, actual.into_inner());
+
+    // `ZRANGE` members hold the struct packed as a RESP reply
+    let packed = serde_redis::resp::to_vec(&expected[0].0).unwrap();
+    let value = Value::Bulk(vec![
+        Value::Data(packed.clone()),
+        Value::Data(b"2.5".to_vec()),
+    ]);
+    let de = Deserializer::new(&value);
+    let actual: Pairs<Member, f64> = Deserialize::deserialize(de).unwrap();
+    assert_eq!(expected[..1], actual[..]);
+
+    let mut reply = format!("*2\r\n${}\r\n", packed.len()).into_bytes();
+    reply.extend_from_slice(&packed);
+    reply.extend_from_slice(b"\r\n$3\r\n2.5\r\n");
+    let actual: Pairs<Member, f64> = serde_redis::resp::from_slice(&reply).unwrap();
+    assert_eq!(expected[..1], actual[..]);
+
+    let value = redis_value!(["ada", "2.5"]);
+    let de = Deserializer::new(&value);
+    assert!(<Pairs<Member, f64>>::deserialize(de).is_err());
+}
+
+#[test]
+fn deserialize_unit_from_nil() {
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Marker;
+
+    let value = serde_redis::to_redis_value(&()).unwrap();
+    assert_eq!(Value::Nil, value);
+    let de = Deserializer::new(&value);
+    <()>::deserialize(de).unwrap();
+
+    let value = serde_redis::to_redis_value(&Marker).unwrap();
+    let de = Deserializer::new(&value);
+    assert_eq!(Marker, Marker::deserialize(de).unwrap());
+}
+
+#[test]
+fn deserialize_int_out_of_range() {
+    let value = Value::Int(300);
+    let de = Deserializer::new(&value);
+    assert!(matches!(
+        u8::deserialize(de),
+        Err(decode::Error::WrongValue(_))
+    ));
+
+    let value = Value::Int(-1);
+    let de = Deserializer::new(&value);
+    assert!(u64::deserialize(de).is_err());
+
+    let value = Value::Int(255);
+    let de = Deserializer::new(&value);
+    assert_eq!(255, u8::deserialize(de).unwrap());
+
+    let value = Value::Int(-3);
+    let de = Deserializer::new(&value);
+    assert_eq!(-3.0, f64::deserialize(de).unwrap());
+}
+
+#[test]
+fn deserialize_option_bulk() {
+    let v = redis_value!(["a"]);
+
+    let de = Deserializer::new(&v);
+    let actual: Option<Vec<String>> = Deserialize::deserialize(de).unwrap();
+
+    assert_eq!(Some(vec!["a".to_owned()]), actual);
+}
+
+#[test]
+fn deserialize_status_string() {
+    let v = redis_value!(status("PONG"));
+
+    let de = Deserializer::new(&v);
+    let actual: String = Deserialize::deserialize(de).unwrap();
+
+    assert_eq!("PONG", actual);
+}
+
+#[test]
+fn deserialize_status_and_okay_options() {
+    let status = redis_value!(status("PONG"));
+    let okay = redis_value!(ok);
+
+    let actual: Option<String> = Deserialize::deserialize(Deserializer::new(&status)).unwrap();
+    assert_eq!(Some("PONG".to_owned()), actual);
+
+    let actual: Option<String> = Deserialize::deserialize(Deserializer::new(&okay)).unwrap();
+    assert_eq!(Some("OK".to_owned()), actual);
+
+    let actual: String = Deserialize::deserialize(Deserializer::new(&okay)).unwrap();
+    assert_eq!("OK", actual);
+}
+
+#[test]
+fn deserialize_through_mut_ref_then_end() {
+    let values = vec![Cow::Owned(redis_value!("1")), Cow::Owned(redis_value!("2"))];
+
+    let mut de = Deserializer::new(values);
+    let first: u8 = Deserialize::deserialize(&mut de).unwrap();
+    assert_eq!(1, first);
+
+    match de.end() {
+        Err(decode::Error::TrailingValues(1)) => (),
+        other => panic!("expected trailing values, got {:?}", other),
+    }
+
+    let second: u8 = Deserialize::deserialize(&mut de).unwrap();
+    assert_eq!(2, second);
+    de.end().unwrap();
+}
+
+#[test]
+fn stream_deserializer_yields_each_value() {
+    let values = vec![
+        Cow::Owned(redis_value!(nil)),
+        Cow::Owned(redis_value!("x")),
+        Cow::Owned(redis_value!(3)),
+        Cow::Owned(redis_value!("y")),
+    ];
+
+    let actual = StreamDeserializer::<Option<String>>::new(values)
+        .map(|v| v.ok())
+        .collect::<Vec<_>>();
+
+    // The error ends the stream
+    assert_eq!(vec![Some(None), Some(Some("x".to_owned())), None], actual);
+}
+
+#[test]
+fn stream_deserializer_stops_after_error() {
+    // Fails without reading its value
+    #[derive(Debug)]
+    struct Never;
+
+    impl<'de> Deserialize<'de> for Never {
+        fn deserialize<D>(_: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            Err(serde::de::Error::custom("never"))
+        }
+    }
+
+    let values = vec![Cow::Owned(redis_value!(1)), Cow::Owned(redis_value!(2))];
+    let mut stream = StreamDeserializer::<Never>::new(values);
+
+    assert!(stream.next().unwrap().is_err());
+    assert!(stream.next().is_none());
+    assert_eq!((0, Some(0)), stream.size_hint());
+}
+
+#[test]
+fn stream_deserializer_over_bulk_items() {
+    let v = redis_value!([["a", 1], ["b", 2]]);
+    let items = match v {
+        Value::Bulk(ref items) => items.iter().map(Cow::Borrowed).collect::<Vec<_>>(),
+        _ => unreachable!(),
+    };
+
+    let actual = Deserializer::new(items)
+        .into_iter::<(String, u32)>()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(vec![("a".to_owned(), 1), ("b".to_owned(), 2)], actual);
+}
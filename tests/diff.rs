@@ -0,0 +1,100 @@
+#[macro_use]
+extern crate serde_derive;
+
+use serde_redis::diff;
+
+#[derive(Serialize)]
+struct User {
+    name: String,
+    email: Option<String>,
+    visits: u32,
+}
+
+#[test]
+fn diff_identical_is_empty() {
+    let user = User {
+        name: "ada".to_owned(),
+        email: None,
+        visits: 3,
+    };
+
+    let diff = diff(&user, &user).unwrap();
+
+    assert!(diff.is_empty());
+    assert!(diff.cmds("user:1").is_empty());
+}
+
+#[test]
+fn diff_sets_changed_and_deletes_cleared_fields() {
+    let old = User {
+        name: "ada".to_owned(),
+        email: Some("ada@example.com".to_owned()),
+        visits: 3,
+    };
+    let new = User {
+        name: "ada".to_owned(),
+        email: None,
+        visits: 4,
+    };
+
+    let diff = diff(&old, &new).unwrap();
+
+    assert_eq!(vec![(b"visits".to_vec(), b"4".to_vec())], diff.set);
+    assert_eq!(vec![b"email".to_vec()], diff.del);
+
+    let cmds = diff.cmds("user:1");
+    assert_eq!(2, cmds.len());
+    assert_eq!(
+        redis::cmd("HSET")
+            .arg("user:1")
+            .arg("visits")
+            .arg("4")
+            .get_packed_command(),
+        cmds[0].get_packed_command()
+    );
+    assert_eq!(
+        redis::cmd("HDEL")
+            .arg("user:1")
+            .arg("email")
+            .get_packed_command(),
+        cmds[1].get_packed_command()
+    );
+}
+
+#[test]
+fn diff_sets_newly_present_field() {
+    let old = User {
+        name: "ada".to_owned(),
+        email: None,
+        visits: 3,
+    };
+    let new = User {
+        name: "ada".to_owned(),
+        email: Some("ada@example.com".to_owned()),
+        visits: 3,
+    };
+
+    let diff = diff(&old, &new).unwrap();
+
+    assert_eq!(
+        vec![(b"email".to_vec(), b"ada@example.com".to_vec())],
+        diff.set
+    );
+    assert!(diff.del.is_empty());
+}
+
+#[test]
+fn diff_deletes_keys_removed_from_map() {
+    use std::collections::BTreeMap;
+
+    let mut old = BTreeMap::new();
+    old.insert("a", "1");
+    old.insert("b", "2");
+    let mut new = old.clone();
+    new.remove("a");
+
+    let diff = diff(&old, &new).unwrap();
+
+    assert!(diff.set.is_empty());
+    assert_eq!(vec![b"a".to_vec()], diff.del);
+}
@@ -0,0 +1,156 @@
+#[macro_use]
+extern crate serde_derive;
+
+use std::collections::BTreeMap;
+
+use redis::Value;
+use serde_redis::encode::{self, to_hash_fields};
+use serde_redis::{from_redis_value, to_redis_value};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+enum Fruit {
+    Orange,
+    Apple,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Simple {
+    a: String,
+    n: u32,
+    ratio: f64,
+    ok: bool,
+    fruit: Fruit,
+    nickname: Option<String>,
+}
+
+fn simple() -> Simple {
+    Simple {
+        a: "apple".to_owned(),
+        n: 7,
+        ratio: 0.5,
+        ok: true,
+        fruit: Fruit::Apple,
+        nickname: None,
+    }
+}
+
+#[test]
+fn serialize_scalars() {
+    assert_eq!(Value::Data(b"5".to_vec()), to_redis_value(&5u8).unwrap());
+    assert_eq!(Value::Data(b"-5".to_vec()), to_redis_value(&-5i64).unwrap());
+    assert_eq!(Value::Data(b"1".to_vec()), to_redis_value(&true).unwrap());
+    assert_eq!(Value::Data(b"hi".to_vec()), to_redis_value("hi").unwrap());
+    assert_eq!(Value::Nil, to_redis_value(&None::<u8>).unwrap());
+}
+
+#[test]
+fn serialize_float_redis_spellings() {
+    assert_eq!(
+        Value::Data(b"1.5".to_vec()),
+        to_redis_value(&1.5f64).unwrap()
+    );
+    assert_eq!(
+        Value::Data(b"inf".to_vec()),
+        to_redis_value(&f64::INFINITY).unwrap()
+    );
+    assert_eq!(
+        Value::Data(b"-inf".to_vec()),
+        to_redis_value(&f32::NEG_INFINITY).unwrap()
+    );
+}
+
+#[test]
+fn serialize_struct_as_flat_pairs() {
+    let actual = to_redis_value(&simple()).unwrap();
+
+    let expected = Value::Bulk(vec![
+        Value::Data(b"a".to_vec()),
+        Value::Data(b"apple".to_vec()),
+        Value::Data(b"n".to_vec()),
+        Value::Data(b"7".to_vec()),
+        Value::Data(b"ratio".to_vec()),
+        Value::Data(b"0.5".to_vec()),
+        Value::Data(b"ok".to_vec()),
+        Value::Data(b"1".to_vec()),
+        Value::Data(b"fruit".to_vec()),
+        Value::Data(b"Apple".to_vec()),
+        Value::Data(b"nickname".to_vec()),
+        Value::Nil,
+    ]);
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn serialize_struct_roundtrip() {
+    let value = to_redis_value(&simple()).unwrap();
+
+    let actual: Simple = from_redis_value(value).unwrap();
+
+    assert_eq!(simple(), actual);
+}
+
+#[test]
+fn serialize_nested_seq_roundtrip() {
+    let expected = vec![vec![1u8, 2], vec![], vec![3]];
+
+    let value = to_redis_value(&expected).unwrap();
+    let actual: Vec<Vec<u8>> = from_redis_value(value).unwrap();
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn serialize_newtype_variant_is_error() {
+    #[derive(Serialize)]
+    enum Shape {
+        Circle(f64),
+    }
+
+    match to_redis_value(&Shape::Circle(1.0)) {
+        Err(encode::Error::UnsupportedType(_)) => {}
+        other => panic!("expected UnsupportedType, got {:?}", other),
+    }
+}
+
+#[test]
+fn hash_fields_from_struct() {
+    let fields = to_hash_fields(&simple()).unwrap();
+
+    let names = fields.iter().map(|(f, _)| &f[..]).collect::<Vec<_>>();
+    assert_eq!(
+        vec![&b"a"[..], b"n", b"ratio", b"ok", b"fruit", b"nickname"],
+        names
+    );
+    assert_eq!(Value::Nil, fields[5].1);
+}
+
+#[test]
+fn hash_fields_from_map() {
+    let mut map = BTreeMap::new();
+    map.insert("x", 1);
+    map.insert("y", 2);
+
+    let fields = to_hash_fields(&map).unwrap();
+
+    let expected = vec![
+        (b"x".to_vec(), Value::Data(b"1".to_vec())),
+        (b"y".to_vec(), Value::Data(b"2".to_vec())),
+    ];
+    assert_eq!(expected, fields);
+}
+
+#[test]
+fn hash_fields_reject_nested_values() {
+    #[derive(Serialize)]
+    struct Nested {
+        tags: Vec<String>,
+    }
+
+    let nested = Nested {
+        tags: vec!["a".to_owned()],
+    };
+
+    assert!(to_hash_fields(&nested).is_err());
+    assert!(to_hash_fields(&5u8).is_err());
+    assert!(to_hash_fields(&vec!["a", "b"]).is_err());
+}
@@ -0,0 +1,112 @@
+#[macro_use]
+extern crate serde_derive;
+
+mod common;
+
+use common::Replay;
+use redis::Value;
+use serde_redis::RedisSerdeExt;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct User {
+    name: String,
+    visits: u32,
+    email: Option<String>,
+}
+
+#[test]
+fn hget_struct_decodes_hgetall() {
+    let mut con = Replay::new(vec![Value::Bulk(vec![
+        Value::Data(b"name".to_vec()),
+        Value::Data(b"ada".to_vec()),
+        Value::Data(b"visits".to_vec()),
+        Value::Data(b"3".to_vec()),
+    ])]);
+
+    let user: User = con.hget_struct("user:1").unwrap();
+
+    let expected = User {
+        name: "ada".to_owned(),
+        visits: 3,
+        email: None,
+    };
+    assert_eq!(expected, user);
+    assert_eq!(
+        redis::cmd("HGETALL").arg("user:1").get_packed_command(),
+        con.sent[0]
+    );
+}
+
+#[test]
+fn hset_struct_skips_none_fields() {
+    let mut con = Replay::new(vec![Value::Int(2)]);
+    let user = User {
+        name: "ada".to_owned(),
+        visits: 3,
+        email: None,
+    };
+
+    con.hset_struct("user:1", &user).unwrap();
+
+    let expected = redis::cmd("HSET")
+        .arg("user:1")
+        .arg("name")
+        .arg("ada")
+        .arg("visits")
+        .arg("3")
+        .get_packed_command();
+    assert_eq!(vec![expected], con.sent);
+}
+
+#[test]
+fn hset_struct_rejects_non_hash() {
+    let mut con = Replay::default();
+
+    assert!(con.hset_struct("user:1", &vec![1, 2]).is_err());
+    assert!(con.sent.is_empty());
+}
+
+#[test]
+fn get_as_handles_missing_key() {
+    let mut con = Replay::new(vec![Value::Data(b"42".to_vec()), Value::Nil]);
+
+    let present: Option<u64> = con.get_as("counter").unwrap();
+    let missing: Option<u64> = con.get_as("other").unwrap();
+
+    assert_eq!(Some(42), present);
+    assert_eq!(None, missing);
+}
+
+#[test]
+fn mget_lrange_smembers_decode_elements() {
+    let mut con = Replay::new(vec![
+        Value::Bulk(vec![Value::Data(b"1".to_vec()), Value::Nil]),
+        Value::Bulk(vec![Value::Data(b"a".to_vec()), Value::Data(b"b".to_vec())]),
+        Value::Bulk(vec![Value::Data(b"7".to_vec())]),
+    ]);
+
+    let mget: Vec<Option<u8>> = con.mget_as(&["x", "y"]).unwrap();
+    let lrange: Vec<String> = con.lrange_as("list", 0, -1).unwrap();
+    let smembers: Vec<u16> = con.smembers_as("set").unwrap();
+
+    assert_eq!(vec![Some(1), None], mget);
+    assert_eq!(vec!["a".to_owned(), "b".to_owned()], lrange);
+    assert_eq!(vec![7], smembers);
+    assert_eq!(
+        redis::cmd("LRANGE")
+            .arg("list")
+            .arg(0)
+            .arg(-1)
+            .get_packed_command(),
+        con.sent[1]
+    );
+}
+
+#[test]
+fn decode_errors_become_type_errors() {
+    let mut con = Replay::new(vec![Value::Data(b"nope".to_vec())]);
+
+    let err = con.get_as::<_, u64>("counter").unwrap_err();
+
+    assert_eq!(redis::ErrorKind::TypeError, err.kind());
+}
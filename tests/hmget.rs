@@ -0,0 +1,123 @@
+#[macro_use]
+extern crate serde_derive;
+
+use redis::Value;
+use serde_redis::hmget;
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Profile {
+    name: String,
+    #[serde(rename = "v")]
+    visits: u32,
+    nickname: Option<String>,
+    #[serde(skip)]
+    cached: bool,
+}
+
+#[test]
+fn fields_follow_declaration_order_and_renames() {
+    let fields = hmget::fields::<Profile>().unwrap();
+
+    assert_eq!(&["name", "v", "nickname"], fields);
+}
+
+#[test]
+fn fields_of_newtype_struct_are_inner_fields() {
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Wrapper(Profile);
+
+    let fields = hmget::fields::<Wrapper>().unwrap();
+
+    assert_eq!(&["name", "v", "nickname"], fields);
+}
+
+#[test]
+fn fields_of_non_struct_is_error() {
+    assert!(hmget::fields::<Vec<String>>().is_err());
+    assert!(hmget::fields::<std::collections::HashMap<String, String>>().is_err());
+}
+
+#[test]
+fn fields_of_struct_with_aliases_is_error() {
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Aliased {
+        #[serde(alias = "nm")]
+        name: String,
+        v: u32,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    #[allow(dead_code)]
+    struct Strict {
+        name: String,
+        #[serde(alias = "visits")]
+        v: Option<u32>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    #[allow(dead_code)]
+    struct Plain {
+        name: String,
+        v: Option<u32>,
+    }
+
+    let err = hmget::fields::<Aliased>().unwrap_err();
+    assert!(err.to_string().contains("alias"), "{}", err);
+    assert!(hmget::fields::<Strict>().is_err());
+    assert!(hmget::cmd::<Aliased, _>("profile:1").is_err());
+    assert_eq!(&["name", "v"], hmget::fields::<Plain>().unwrap());
+}
+
+#[test]
+fn cmd_requests_every_field() {
+    let cmd = hmget::cmd::<Profile, _>("profile:1").unwrap();
+
+    let expected = redis::cmd("HMGET")
+        .arg("profile:1")
+        .arg("name")
+        .arg("v")
+        .arg("nickname")
+        .get_packed_command();
+    assert_eq!(expected, cmd.get_packed_command());
+}
+
+#[test]
+fn from_redis_value_zips_positional_values() {
+    let reply = Value::Bulk(vec![
+        Value::Data(b"ada".to_vec()),
+        Value::Data(b"12".to_vec()),
+        Value::Nil,
+    ]);
+
+    let actual: Profile = hmget::from_redis_value(&reply).unwrap();
+
+    let expected = Profile {
+        name: "ada".to_owned(),
+        visits: 12,
+        nickname: None,
+        cached: false,
+    };
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn from_redis_value_missing_required_field_is_error() {
+    let reply = Value::Bulk(vec![Value::Nil, Value::Int(12), Value::Nil]);
+
+    let actual = hmget::from_redis_value::<Profile, _>(reply);
+
+    assert!(actual.is_err());
+}
+
+#[test]
+fn from_redis_value_length_mismatch_is_error() {
+    let reply = Value::Bulk(vec![Value::Data(b"ada".to_vec())]);
+
+    let actual = hmget::from_redis_value::<Profile, _>(reply);
+
+    assert!(actual.is_err());
+}
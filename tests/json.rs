@@ -0,0 +1,119 @@
+#![cfg(feature = "json")]
+
+#[macro_use]
+extern crate serde_derive;
+
+use serde::de::DeserializeSeed;
+use serde_json::json;
+use serde_redis::json::{from_json, to_json, Mode, Seed};
+use serde_redis::{from_redis_value, redis_value, resp, to_redis_value};
+
+#[test]
+fn array_mode() {
+    let reply = redis_value!(["a", 1, nil, ok, status("PONG"), ["b", "c"]]);
+
+    let value = to_json(&reply, Mode::Array).unwrap();
+
+    assert_eq!(json!(["a", 1, null, "OK", "PONG", ["b", "c"]]), value);
+}
+
+#[test]
+fn map_mode() {
+    let reply = redis_value!({
+        "name": "ada",
+        "visits": 3,
+        "tags": ["a", "b", "a"],
+        "nested": { "inner": nil },
+        "repeated": ["k", 1, "k", 2],
+    });
+
+    let value = to_json(&reply, Mode::Map).unwrap();
+
+    assert_eq!(
+        json!({
+            "name": "ada",
+            "visits": 3,
+            "tags": ["a", "b", "a"],
+            "nested": { "inner": null },
+            "repeated": ["k", 1, "k", 2],
+        }),
+        value
+    );
+}
+
+#[test]
+fn binary_data_as_base64() {
+    let reply = redis_value!([b"\xff\xfe", "text"]);
+
+    let value = to_json(&reply, Mode::Array).unwrap();
+
+    assert_eq!(json!([{ "base64": "//4=" }, "text"]), value);
+    assert_eq!(reply, from_json(&value).unwrap());
+}
+
+#[test]
+fn from_json_matches_serializer() {
+    let value = json!({
+        "name": "ada",
+        "visits": 3,
+        "ratio": 0.5,
+        "admin": false,
+        "email": null,
+        "tags": ["a", "b"],
+        "base64": "not alone",
+    });
+
+    assert_eq!(to_redis_value(&value).unwrap(), from_json(&value).unwrap());
+    // Only a lone field holding valid base64 is read as data
+    assert_eq!(
+        redis_value!({ "base64": "not base64!" }),
+        from_json(&json!({ "base64": "not base64!" })).unwrap()
+    );
+}
+
+#[test]
+fn seed_over_resp3() {
+    let input = b"%2\r\n+name\r\n$3\r\nada\r\n+scores\r\n*3\r\n#t\r\n,1.5\r\n,inf\r\n";
+    let mut de = resp::Deserializer::from_slice(input);
+
+    let value = Seed(Mode::Array).deserialize(&mut de).unwrap();
+
+    assert_eq!(
+        json!({ "name": "ada", "scores": [true, 1.5, "inf"] }),
+        value
+    );
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+enum Reply {
+    Count(i64),
+    Text(String),
+    Items(Vec<Reply>),
+}
+
+#[test]
+fn untagged_enums_decode() {
+    let reply = redis_value!([1, "two", [3]]);
+    let expected = Reply::Items(vec![
+        Reply::Count(1),
+        Reply::Text("two".to_owned()),
+        Reply::Items(vec![Reply::Count(3)]),
+    ]);
+
+    assert_eq!(expected, from_redis_value::<Reply, _>(&reply).unwrap());
+    assert_eq!(
+        expected,
+        resp::from_slice::<Reply>(b"*3\r\n:1\r\n$3\r\ntwo\r\n*1\r\n:3\r\n").unwrap()
+    );
+}
+
+#[test]
+fn deeply_nested_input_is_rejected() {
+    let deep = b"*1\r\n".repeat(1_000_000);
+
+    assert!(matches!(
+        resp::from_slice::<serde_json::Value>(&deep),
+        Err(resp::Error::Syntax(..))
+    ));
+}
@@ -0,0 +1,84 @@
+use redis::{Msg, Value};
+use serde_redis::keyspace::{self, KeyEvent, Op};
+
+fn pmessage(pattern: &str, channel: &str, payload: &str) -> Msg {
+    Msg::from_value(&Value::Bulk(vec![
+        Value::Data(b"pmessage".to_vec()),
+        Value::Data(pattern.as_bytes().to_vec()),
+        Value::Data(channel.as_bytes().to_vec()),
+        Value::Data(payload.as_bytes().to_vec()),
+    ]))
+    .unwrap()
+}
+
+#[test]
+fn keyspace_notification() {
+    let msg = pmessage("__keyspace@*__:*", "__keyspace@0__:user:1", "hset");
+
+    let event = keyspace::from_msg(&msg).unwrap();
+
+    let expected = KeyEvent {
+        db: 0,
+        key: b"user:1".to_vec(),
+        op: Op::Hset,
+    };
+    assert_eq!(expected, event);
+}
+
+#[test]
+fn keyevent_notification() {
+    let msg = pmessage("__keyevent@*__:*", "__keyevent@3__:expired", "session:abc");
+
+    let event = keyspace::from_msg(&msg).unwrap();
+
+    let expected = KeyEvent {
+        db: 3,
+        key: b"session:abc".to_vec(),
+        op: Op::Expired,
+    };
+    assert_eq!(expected, event);
+}
+
+#[test]
+fn key_containing_separator() {
+    let event = KeyEvent::parse("__keyspace@0__:a__:b", b"del").unwrap();
+
+    assert_eq!(b"a__:b", &event.key[..]);
+    assert_eq!(Op::Del, event.op);
+}
+
+#[test]
+fn renamed_ops() {
+    let parse = |op: &str| {
+        KeyEvent::parse("__keyspace@0__:k", op.as_bytes())
+            .unwrap()
+            .op
+    };
+
+    assert_eq!(Op::RenameFrom, parse("rename_from"));
+    assert_eq!(Op::XgroupCreate, parse("xgroup-create"));
+    assert_eq!(Op::Other("hexpired".to_owned()), parse("hexpired"));
+}
+
+#[test]
+fn binary_keys() {
+    let event = KeyEvent::parse(b"__keyspace@1__:blob:\xff\x00", b"set").unwrap();
+    assert_eq!(b"blob:\xff\x00", &event.key[..]);
+
+    let msg = Msg::from_value(&Value::Bulk(vec![
+        Value::Data(b"message".to_vec()),
+        Value::Data(b"__keyevent@1__:del".to_vec()),
+        Value::Data(b"\xfe\xff".to_vec()),
+    ]))
+    .unwrap();
+    let event = keyspace::from_msg(&msg).unwrap();
+    assert_eq!(b"\xfe\xff", &event.key[..]);
+    assert_eq!(Op::Del, event.op);
+}
+
+#[test]
+fn other_channel_is_error() {
+    assert!(KeyEvent::parse("jobs", b"set").is_err());
+    assert!(KeyEvent::parse("__keyspace@x__:k", b"set").is_err());
+    assert!(KeyEvent::parse("__keyspace@0", b"set").is_err());
+}
@@ -0,0 +1,66 @@
+use redis::Value;
+use serde_redis::redis_value;
+
+fn data(s: &str) -> Value {
+    Value::Data(s.as_bytes().to_vec())
+}
+
+#[test]
+fn expressions_as_elements() {
+    let name = String::from("ada");
+    let email: Option<&str> = None;
+    let visits = 3u32;
+
+    let value = redis_value!([name.clone(), -1, visits + 1, email, Some("x"), vec![b'y']]);
+
+    assert_eq!(
+        Value::Bulk(vec![
+            data("ada"),
+            Value::Int(-1),
+            Value::Int(4),
+            Value::Nil,
+            data("x"),
+            data("y"),
+        ]),
+        value
+    );
+}
+
+#[test]
+fn object_values() {
+    let value = redis_value!({
+        "status": status("OK"),
+        "set": ok,
+        "count": -2,
+        "missing": nil,
+        "empty": [],
+        "hash": {},
+    });
+
+    assert_eq!(
+        Value::Bulk(vec![
+            data("status"),
+            Value::Status("OK".to_owned()),
+            data("set"),
+            Value::Okay,
+            data("count"),
+            Value::Int(-2),
+            data("missing"),
+            Value::Nil,
+            data("empty"),
+            Value::Bulk(vec![]),
+            data("hash"),
+            Value::Bulk(vec![]),
+        ]),
+        value
+    );
+}
+
+#[test]
+fn trailing_commas_and_nesting() {
+    assert_eq!(Value::Bulk(vec![]), redis_value!([]));
+    assert_eq!(
+        Value::Bulk(vec![Value::Bulk(vec![Value::Nil, Value::Okay])]),
+        redis_value!([[nil, ok,],])
+    );
+}
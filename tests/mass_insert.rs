@@ -0,0 +1,126 @@
+#[macro_use]
+extern crate serde_derive;
+
+use redis::Value;
+use serde_redis::from_redis_value;
+use serde_redis::mass_insert::{Error, KeyTemplate, Store, Writer};
+use serde_redis::resp::Deserializer;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct User {
+    id: u64,
+    name: String,
+    email: Option<String>,
+}
+
+fn users() -> Vec<User> {
+    vec![
+        User {
+            id: 1,
+            name: "ada".to_owned(),
+            email: Some("ada@example.com".to_owned()),
+        },
+        User {
+            id: 2,
+            name: "bob".to_owned(),
+            email: None,
+        },
+    ]
+}
+
+/// Split written output back into commands
+fn commands(bytes: &[u8]) -> Vec<Vec<String>> {
+    let mut de = Deserializer::from_slice(bytes);
+    let mut commands = Vec::new();
+    while de.end().is_err() {
+        commands.push(serde::Deserialize::deserialize(&mut de).unwrap());
+    }
+    commands
+}
+
+fn new_writer(template: &str) -> Writer<Vec<u8>> {
+    Writer::new(Vec::new(), template.parse().unwrap())
+}
+
+#[test]
+fn hash_reads_back() {
+    let mut writer = new_writer("user:{id}");
+    for user in &users() {
+        writer.write(user).unwrap();
+    }
+    assert_eq!(2, writer.commands());
+
+    let commands = commands(&writer.into_inner());
+    assert_eq!(
+        vec!["HSET", "user:2", "id", "2", "name", "bob"],
+        commands[1]
+    );
+
+    for (command, user) in commands.iter().zip(users()) {
+        let fields = command[2..]
+            .iter()
+            .map(|arg| Value::Data(arg.clone().into_bytes()))
+            .collect();
+        let read: User = from_redis_value(Value::Bulk(fields)).unwrap();
+        assert_eq!(user, read);
+    }
+}
+
+#[test]
+fn string_and_list_with_expire() {
+    let mut writer = new_writer("email:{email}").store(Store::String("id".to_owned()));
+    writer.write(&users()[0]).unwrap();
+    assert_eq!(
+        vec![vec!["SET", "email:ada@example.com", "1"]],
+        commands(&writer.into_inner())
+    );
+
+    let mut writer = new_writer("names")
+        .store(Store::List("name".to_owned()))
+        .expire(60);
+    for user in &users() {
+        writer.write(user).unwrap();
+    }
+    assert_eq!(4, writer.commands());
+    assert_eq!(
+        vec![
+            vec!["RPUSH", "names", "ada"],
+            vec!["EXPIRE", "names", "60"],
+            vec!["RPUSH", "names", "bob"],
+            vec!["EXPIRE", "names", "60"],
+        ],
+        commands(&writer.into_inner())
+    );
+}
+
+#[test]
+fn missing_key_field() {
+    let mut writer = new_writer("email:{email}");
+
+    assert!(matches!(
+        writer.write(&users()[1]),
+        Err(Error::MissingField(ref field)) if field == "email"
+    ));
+    assert_eq!(0, writer.commands());
+}
+
+#[test]
+fn key_template_syntax() {
+    let mut writer = new_writer("{{{name}}}:{id}");
+    writer.write(&users()[0]).unwrap();
+    assert_eq!("{ada}:1", commands(&writer.into_inner())[0][1]);
+
+    for template in &["user:{id", "user:{}", "user:id}"] {
+        assert!(matches!(
+            template.parse::<KeyTemplate>(),
+            Err(Error::Template(_))
+        ));
+    }
+}
+
+#[test]
+fn rejects_records_that_are_not_hashes() {
+    let mut writer = new_writer("k");
+
+    assert!(matches!(writer.write(&[1, 2]), Err(Error::Encode(_))));
+}
@@ -0,0 +1,203 @@
+#[macro_use]
+extern crate serde_derive;
+
+use redis::Value;
+use serde_redis::decode;
+use serde_redis::pipeline::{self, Slot};
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct User {
+    name: String,
+}
+
+fn user_hash(name: &str) -> Value {
+    Value::Bulk(vec![
+        Value::Data(b"name".to_vec()),
+        Value::Data(name.as_bytes().to_vec()),
+    ])
+}
+
+fn pipe() -> redis::Pipeline {
+    let mut pipe = redis::pipe();
+    pipe.cmd("HGETALL")
+        .arg("user:1")
+        .cmd("GET")
+        .arg("visits:1")
+        .cmd("LRANGE")
+        .arg("tags:1")
+        .arg(0)
+        .arg(-1);
+    pipe
+}
+
+#[test]
+fn from_replies_decodes_mixed_tuple() {
+    let replies = Value::Bulk(vec![
+        user_hash("ada"),
+        Value::Nil,
+        Value::Bulk(vec![Value::Data(b"admin".to_vec())]),
+    ]);
+
+    let (user, visits, tags): (User, Option<u64>, Vec<String>) =
+        pipeline::from_replies(&pipe(), &replies).unwrap();
+
+    assert_eq!(
+        User {
+            name: "ada".to_owned()
+        },
+        user
+    );
+    assert_eq!(None, visits);
+    assert_eq!(vec!["admin".to_owned()], tags);
+}
+
+#[test]
+fn from_replies_decodes_struct_by_position() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Replies {
+        user: User,
+        visits: u64,
+        tags: Vec<String>,
+    }
+
+    let replies = Value::Bulk(vec![
+        user_hash("ada"),
+        Value::Data(b"3".to_vec()),
+        Value::Bulk(vec![]),
+    ]);
+
+    let actual: Replies = pipeline::from_replies(&pipe(), replies).unwrap();
+
+    assert_eq!(3, actual.visits);
+    assert!(actual.tags.is_empty());
+}
+
+#[test]
+fn from_replies_reports_failing_slot_and_command() {
+    let replies = Value::Bulk(vec![
+        user_hash("ada"),
+        Value::Data(b"not a number".to_vec()),
+        Value::Bulk(vec![]),
+    ]);
+
+    let err = pipeline::from_replies::<(User, u64, Vec<String>), _>(&pipe(), replies).unwrap_err();
+
+    assert_eq!(1, err.index);
+    assert_eq!(Some("GET".to_owned()), err.command);
+    assert!(err.to_string().starts_with("slot 1 (GET): "));
+}
+
+#[test]
+fn from_replies_reports_only_the_command_name() {
+    let mut pipe = redis::pipe();
+    pipe.cmd("SET")
+        .arg("token")
+        .arg("s3cret")
+        .cmd("GET")
+        .arg("a");
+    let replies = Value::Bulk(vec![Value::Okay, Value::Data(b"x".to_vec())]);
+
+    let err = pipeline::from_replies::<(String, u8), _>(&pipe, replies).unwrap_err();
+
+    assert_eq!(1, err.index);
+    assert_eq!(Some("GET".to_owned()), err.command);
+    assert!(!err.to_string().contains("s3cret"));
+}
+
+#[test]
+fn from_replies_omits_command_when_some_are_ignored() {
+    let mut pipe = redis::pipe();
+    pipe.atomic()
+        .get("a")
+        .set("b", 1)
+        .ignore()
+        .cmd("INCR")
+        .arg("c");
+    let replies = Value::Bulk(vec![Value::Nil, Value::Data(b"x".to_vec())]);
+
+    let err = pipeline::from_replies::<(Option<u8>, u8), _>(&pipe, replies).unwrap_err();
+    assert_eq!(1, err.index);
+    assert_eq!(None, err.command);
+}
+
+#[test]
+fn from_replies_omits_command_when_slots_do_not_line_up() {
+    let replies = Value::Bulk(vec![Value::Data(b"x".to_vec())]);
+
+    let err = pipeline::from_replies::<(u8,), _>(&pipe(), replies).unwrap_err();
+
+    assert_eq!(0, err.index);
+    assert_eq!(None, err.command);
+}
+
+#[test]
+fn from_replies_rejects_trailing_replies() {
+    let replies = Value::Bulk(vec![
+        user_hash("ada"),
+        Value::Data(b"3".to_vec()),
+        Value::Bulk(vec![]),
+    ]);
+
+    let err = pipeline::from_replies::<(User, u64), _>(&pipe(), replies).unwrap_err();
+
+    assert_eq!(2, err.index);
+    assert_eq!(Some("LRANGE".to_owned()), err.command);
+    assert!(matches!(err.error, decode::Error::TrailingValues(1)));
+}
+
+#[test]
+fn slot_keeps_the_decode_error() {
+    let replies = Value::Bulk(vec![Value::Data(b"oops".to_vec())]);
+
+    let (slot,): (Slot<u8>,) = pipeline::from_replies(&redis::pipe(), replies.clone()).unwrap();
+    assert!(matches!(
+        slot.into_result(),
+        Err(decode::Error::ParseInt(_))
+    ));
+
+    let err = pipeline::from_replies::<(u8,), _>(&redis::pipe(), replies).unwrap_err();
+    assert!(matches!(err.error, decode::Error::ParseInt(_)));
+}
+
+#[test]
+fn slot_collects_per_slot_results() {
+    let replies = Value::Bulk(vec![
+        Value::Data(b"1".to_vec()),
+        Value::Data(b"oops".to_vec()),
+        Value::Data(b"3".to_vec()),
+    ]);
+
+    let slots: Vec<Slot<u8>> = pipeline::from_replies(&redis::pipe(), replies).unwrap();
+
+    let results = slots
+        .into_iter()
+        .map(|slot| slot.into_result().ok())
+        .collect::<Vec<_>>();
+    assert_eq!(vec![Some(1), None, Some(3)], results);
+}
+
+#[test]
+fn nested_slots_keep_the_decode_error() {
+    let replies = Value::Bulk(vec![Value::Bulk(vec![
+        Value::Data(b"1".to_vec()),
+        Value::Data(b"oops".to_vec()),
+    ])]);
+
+    let (slots,): (Vec<Slot<Slot<u8>>>,) =
+        pipeline::from_replies(&redis::pipe(), &replies).unwrap();
+
+    let results = slots
+        .into_iter()
+        .map(|slot| slot.into_result().unwrap().into_result())
+        .collect::<Vec<_>>();
+    assert!(matches!(results[0], Ok(1)));
+    assert!(matches!(results[1], Err(decode::Error::ParseInt(_))));
+
+    // Nothing carries over into the next call
+    let replies = Value::Bulk(vec![Value::Nil]);
+    let (slot,): (Slot<String>,) = pipeline::from_replies(&redis::pipe(), replies).unwrap();
+    assert!(matches!(
+        slot.into_result(),
+        Err(decode::Error::WrongValue(_))
+    ));
+}
@@ -0,0 +1,99 @@
+#[macro_use]
+extern crate serde_derive;
+
+use redis::{Msg, Value};
+use serde_redis::pubsub::{self, Codec, RedisCodec};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Job {
+    id: u64,
+    queue: String,
+    tags: Vec<String>,
+    retry: Option<u32>,
+}
+
+fn job() -> Job {
+    Job {
+        id: 7,
+        queue: "mail".to_owned(),
+        tags: vec!["a".to_owned(), "b".to_owned()],
+        retry: None,
+    }
+}
+
+fn msg(payload: Vec<u8>) -> Msg {
+    Msg::from_value(&Value::Bulk(vec![
+        Value::Data(b"message".to_vec()),
+        Value::Data(b"jobs".to_vec()),
+        Value::Data(payload),
+    ]))
+    .unwrap()
+}
+
+#[test]
+fn redis_codec_round_trip() {
+    let payload = RedisCodec.encode(&job()).unwrap();
+
+    let decoded: Job = pubsub::from_msg(&msg(payload)).unwrap();
+
+    assert_eq!(job(), decoded);
+}
+
+#[test]
+fn redis_codec_scalar_payload() {
+    let payload = RedisCodec.encode("hello").unwrap();
+    assert_eq!(b"$5\r\nhello\r\n".to_vec(), payload);
+
+    let decoded: String = pubsub::from_msg(&msg(payload)).unwrap();
+    assert_eq!("hello", decoded);
+}
+
+#[test]
+fn redis_codec_nested_payload() {
+    let value = vec![vec![1u8, 2], vec![], vec![3]];
+    let payload = RedisCodec.encode(&value).unwrap();
+
+    let decoded: Vec<Vec<u8>> = RedisCodec.decode(&payload).unwrap();
+
+    assert_eq!(value, decoded);
+}
+
+#[test]
+fn redis_codec_malformed_payload_is_error() {
+    let decoded = pubsub::from_msg::<Job>(&msg(b"not resp".to_vec()));
+
+    assert!(decoded.is_err());
+}
+
+#[test]
+fn redis_codec_wrong_shape_is_decode_error() {
+    let payload = RedisCodec.encode(&42).unwrap();
+
+    match pubsub::from_msg::<Job>(&msg(payload)) {
+        Err(pubsub::Error::Decode(_)) => (),
+        other => panic!("expected decode error, got {:?}", other),
+    }
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn json_codec_round_trip() {
+    use serde_redis::pubsub::JsonCodec;
+
+    let payload = br#"{"id":7,"queue":"mail","tags":["a","b"],"retry":null}"#.to_vec();
+    let decoded: Job = pubsub::from_msg_with(&JsonCodec, &msg(payload.clone())).unwrap();
+
+    assert_eq!(job(), decoded);
+    assert_eq!(payload, JsonCodec.encode(&job()).unwrap());
+}
+
+#[cfg(feature = "msgpack")]
+#[test]
+fn msgpack_codec_round_trip() {
+    use serde_redis::pubsub::MsgpackCodec;
+
+    let payload = MsgpackCodec.encode(&job()).unwrap();
+    let decoded: Job = pubsub::from_msg_with(&MsgpackCodec, &msg(payload)).unwrap();
+
+    assert_eq!(job(), decoded);
+}
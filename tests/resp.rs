@@ -0,0 +1,571 @@
+#[macro_use]
+extern crate serde_derive;
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Cursor, Read};
+
+use serde::de::DeserializeOwned;
+use serde_redis::from_redis_value;
+use serde_redis::resp::{self, Deserializer, Error, Value as RespValue};
+
+/// Decode `input` natively and through `redis::Value`, and check both agree
+fn parity<T>(input: &[u8]) -> T
+where
+    T: DeserializeOwned + PartialEq + std::fmt::Debug,
+{
+    let native: T = resp::from_slice(input).unwrap();
+    let value = redis::parse_redis_value(input).unwrap();
+    let tree: T = from_redis_value(value).unwrap();
+
+    assert_eq!(tree, native);
+    native
+}
+
+#[test]
+fn scalars() {
+    assert_eq!(42u32, parity::<u32>(b"$2\r\n42\r\n"));
+    assert_eq!(-7i64, parity::<i64>(b":-7\r\n"));
+    assert_eq!(1.5f64, parity::<f64>(b"$3\r\n1.5\r\n"));
+    assert!(parity::<bool>(b"$1\r\n1\r\n"));
+    assert_eq!("PONG", parity::<String>(b"+PONG\r\n"));
+    assert_eq!(None, parity::<Option<String>>(b"$-1\r\n"));
+    assert_eq!(None, parity::<Option<Vec<u8>>>(b"*-1\r\n"));
+}
+
+#[test]
+fn status_replies_as_options() {
+    assert_eq!(Some("PONG".to_owned()), parity(b"+PONG\r\n"));
+    assert_eq!(Some("OK".to_owned()), parity(b"+OK\r\n"));
+    assert_eq!("OK", parity::<String>(b"+OK\r\n"));
+}
+
+#[test]
+fn struct_from_flat_pairs() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct User {
+        name: String,
+        visits: u32,
+        email: Option<String>,
+    }
+
+    let input = b"*4\r\n$4\r\nname\r\n$3\r\nada\r\n$6\r\nvisits\r\n:3\r\n";
+
+    let expected = User {
+        name: "ada".to_owned(),
+        visits: 3,
+        email: None,
+    };
+    assert_eq!(expected, parity::<User>(input));
+}
+
+#[test]
+fn nested_collections() {
+    let input = b"*2\r\n*2\r\n$1\r\na\r\n$1\r\n1\r\n*0\r\n";
+    let expected = vec![vec!["a".to_owned(), "1".to_owned()], vec![]];
+    assert_eq!(expected, parity::<Vec<Vec<String>>>(input));
+
+    let input = b"*4\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n$1\r\n2\r\n";
+    let map = parity::<HashMap<String, u8>>(input);
+    assert_eq!(Some(&2), map.get("b"));
+}
+
+#[test]
+fn unit_enum() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    enum Fruit {
+        Orange,
+        Apple,
+    }
+
+    assert_eq!(Fruit::Apple, parity::<Fruit>(b"$5\r\nApple\r\n"));
+}
+
+#[test]
+fn borrows_from_input() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Entry<'a> {
+        key: &'a str,
+        #[serde(with = "serde_bytes")]
+        raw: &'a [u8],
+    }
+
+    let input = b"*4\r\n$3\r\nkey\r\n$2\r\nk1\r\n$3\r\nraw\r\n$3\r\n\x00\r\n\r\n";
+    let entry: Entry = resp::from_slice(input).unwrap();
+
+    assert_eq!("k1", entry.key);
+    assert_eq!(b"\x00\r\n", entry.raw);
+}
+
+#[test]
+fn skips_unknown_fields_of_any_shape() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Name {
+        name: String,
+    }
+
+    let input = b"*4\r\n$5\r\nextra\r\n*2\r\n:1\r\n:2\r\n$4\r\nname\r\n$3\r\nada\r\n";
+    let name: Name = resp::from_slice(input).unwrap();
+
+    assert_eq!("ada", name.name);
+}
+
+#[test]
+fn tuple_ignores_extra_elements() {
+    let input = b"*3\r\n:1\r\n:2\r\n:3\r\n";
+    let (a, b): (u8, u8) = resp::from_slice(input).unwrap();
+
+    assert_eq!((1, 2), (a, b));
+}
+
+#[test]
+fn consecutive_replies() {
+    let input = b"+OK\r\n:5\r\n$-1\r\n";
+    let mut de = Deserializer::from_slice(input);
+
+    let ok: String = serde::Deserialize::deserialize(&mut de).unwrap();
+    let count: u32 = serde::Deserialize::deserialize(&mut de).unwrap();
+    let missing: Option<String> = serde::Deserialize::deserialize(&mut de).unwrap();
+
+    assert_eq!(("OK".to_owned(), 5, None), (ok, count, missing));
+    de.end().unwrap();
+}
+
+#[test]
+fn error_reply() {
+    match resp::from_slice::<String>(b"-ERR unknown command\r\n") {
+        Err(Error::Server(msg)) => assert_eq!("ERR unknown command", msg),
+        other => panic!("expected server error, got {:?}", other),
+    }
+}
+
+#[test]
+fn malformed_input() {
+    assert!(matches!(
+        resp::from_slice::<String>(b"$5\r\nab"),
+        Err(Error::Eof)
+    ));
+    assert!(matches!(
+        resp::from_slice::<String>(b"$2\r\nabc\r\n"),
+        Err(Error::Syntax(6, _))
+    ));
+    assert!(matches!(
+        resp::from_slice::<String>(b"?x\r\n"),
+        Err(Error::Syntax(0, _))
+    ));
+    assert!(matches!(
+        resp::from_slice::<String>(b"+OK\r\n+OK\r\n"),
+        Err(Error::TrailingBytes(5))
+    ));
+}
+
+#[test]
+fn wrong_type_is_decode_error() {
+    assert!(matches!(
+        resp::from_slice::<u32>(b"*0\r\n"),
+        Err(Error::Decode(_))
+    ));
+}
+
+#[test]
+fn integers_out_of_range_are_decode_errors() {
+    assert!(matches!(
+        resp::from_slice::<u64>(b":-1\r\n"),
+        Err(Error::Decode(_))
+    ));
+    assert!(matches!(
+        resp::from_slice::<u8>(b":256\r\n"),
+        Err(Error::Decode(_))
+    ));
+    assert_eq!(255u8, resp::from_slice::<u8>(b":255\r\n").unwrap());
+}
+
+#[test]
+fn deeply_nested_input_is_rejected() {
+    let deep = b"*1\r\n".repeat(1_000_000);
+    let attributes = b"|1\r\n".repeat(1_000_000);
+
+    assert!(matches!(resp::parse_value(&deep), Err(Error::Syntax(..))));
+    assert!(matches!(
+        resp::from_slice::<serde::de::IgnoredAny>(&deep),
+        Err(Error::Syntax(..))
+    ));
+    assert!(matches!(
+        resp::from_slice::<String>(&attributes),
+        Err(Error::Syntax(..))
+    ));
+
+    let mut shallow = b"*1\r\n".repeat(100);
+    shallow.extend_from_slice(b":1\r\n");
+    assert!(resp::parse_value(&shallow).is_ok());
+}
+
+#[test]
+fn resp3_map_into_struct_and_map() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Server {
+        server: String,
+        proto: u8,
+        modules: Vec<String>,
+    }
+
+    let input =
+        b"%3\r\n$6\r\nserver\r\n$5\r\nredis\r\n$5\r\nproto\r\n:3\r\n$7\r\nmodules\r\n*0\r\n";
+
+    let hello: Server = resp::from_slice(input).unwrap();
+    let expected = Server {
+        server: "redis".to_owned(),
+        proto: 3,
+        modules: vec![],
+    };
+    assert_eq!(expected, hello);
+
+    let input = b"%2\r\n+a\r\n,1.5\r\n+b\r\n,inf\r\n";
+    let map: HashMap<String, f64> = resp::from_slice(input).unwrap();
+    assert_eq!(Some(&1.5), map.get("a"));
+    assert_eq!(Some(&f64::INFINITY), map.get("b"));
+}
+
+#[test]
+fn resp3_scalars() {
+    assert!(resp::from_slice::<bool>(b"#t\r\n").unwrap());
+    assert!(!resp::from_slice::<bool>(b"#f\r\n").unwrap());
+    assert_eq!(0.25, resp::from_slice::<f64>(b",0.25\r\n").unwrap());
+    assert_eq!(None, resp::from_slice::<Option<u8>>(b"_\r\n").unwrap());
+    assert_eq!(
+        170141183460469231731687303715884105727i128,
+        resp::from_slice(b"(170141183460469231731687303715884105727\r\n").unwrap()
+    );
+    assert_eq!(
+        "Some string",
+        resp::from_slice::<&str>(b"=15\r\ntxt:Some string\r\n").unwrap()
+    );
+    assert!(resp::from_slice::<i64>(b",1.5\r\n").is_err());
+}
+
+#[test]
+fn resp3_set_and_push_as_sequences() {
+    let members: Vec<String> = resp::from_slice(b"~2\r\n+a\r\n+b\r\n").unwrap();
+    assert_eq!(vec!["a", "b"], members);
+
+    let (kind, channel, payload): (String, String, String) =
+        resp::from_slice(b">3\r\n$7\r\nmessage\r\n$4\r\njobs\r\n$2\r\nhi\r\n").unwrap();
+    assert_eq!(("message", "jobs", "hi"), (&*kind, &*channel, &*payload));
+}
+
+#[test]
+fn resp3_attributes_are_skipped() {
+    let input = b"|1\r\n+key-popularity\r\n%1\r\n$1\r\na\r\n,0.19\r\n:42\r\n";
+
+    assert_eq!(42, resp::from_slice::<u32>(input).unwrap());
+}
+
+#[test]
+fn resp3_blob_error() {
+    match resp::from_slice::<String>(b"!8\r\nSYNTAX x\r\n") {
+        Err(Error::Server(msg)) => assert_eq!("SYNTAX x", msg),
+        other => panic!("expected server error, got {:?}", other),
+    }
+}
+
+#[test]
+fn resp3_self_describing_any() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(untagged)]
+    enum Scalar {
+        Bool(bool),
+        Double(f64),
+    }
+
+    assert_eq!(Scalar::Bool(true), resp::from_slice(b"#t\r\n").unwrap());
+    assert_eq!(Scalar::Double(2.5), resp::from_slice(b",2.5\r\n").unwrap());
+}
+
+#[test]
+fn parse_resp3_value() {
+    let input = b"%2\r\n+a\r\n~1\r\n#t\r\n+b\r\n=7\r\nmkd:*hi\r\n";
+
+    let expected = RespValue::Map(vec![
+        (
+            RespValue::Status("a".to_owned()),
+            RespValue::Set(vec![RespValue::Boolean(true)]),
+        ),
+        (
+            RespValue::Status("b".to_owned()),
+            RespValue::Verbatim {
+                format: "mkd".to_owned(),
+                text: "*hi".to_owned(),
+            },
+        ),
+    ]);
+    assert_eq!(expected, resp::parse_value(input).unwrap());
+}
+
+#[test]
+fn resp3_value_from_redis_value() {
+    let value = redis::Value::Bulk(vec![redis::Value::Okay, redis::Value::Int(1)]);
+
+    let expected = RespValue::Bulk(vec![RespValue::Status("OK".to_owned()), RespValue::Int(1)]);
+    assert_eq!(expected, RespValue::from(value));
+}
+
+/// Hands out at most `chunk` bytes per `fill_buf`, as a socket might
+struct Chunked<'a> {
+    input: &'a [u8],
+    chunk: usize,
+}
+
+impl<'a> Read for Chunked<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.fill_buf()?.len().min(buf.len());
+        buf[..n].copy_from_slice(&self.input[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<'a> BufRead for Chunked<'a> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let n = self.chunk.min(self.input.len());
+        Ok(&self.input[..n])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.input = &self.input[amt..];
+    }
+}
+
+/// Decode `input` from a slice and from readers of several chunk sizes, and check all agree
+fn reader_parity<T>(input: &[u8]) -> T
+where
+    T: DeserializeOwned + PartialEq + std::fmt::Debug,
+{
+    let sliced: T = resp::from_slice(input).unwrap();
+    for chunk in 1..4 {
+        let read: T = resp::from_reader(Chunked { input, chunk }).unwrap();
+        assert_eq!(sliced, read, "chunks of {} bytes", chunk);
+    }
+
+    let read: T = resp::from_reader(Cursor::new(input)).unwrap();
+    assert_eq!(sliced, read);
+    sliced
+}
+
+#[test]
+fn reader_matches_slice() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct User {
+        name: String,
+        visits: u32,
+        email: Option<String>,
+        tags: Vec<String>,
+    }
+
+    let input = b"*6\r\n$4\r\nname\r\n$3\r\nada\r\n$6\r\nvisits\r\n:3\r\n\
+                  $4\r\ntags\r\n~2\r\n+a\r\n$0\r\n\r\n";
+    let expected = User {
+        name: "ada".to_owned(),
+        visits: 3,
+        email: None,
+        tags: vec!["a".to_owned(), "".to_owned()],
+    };
+    assert_eq!(expected, reader_parity::<User>(input));
+
+    assert_eq!(None, reader_parity::<Option<String>>(b"$-1\r\n"));
+    assert_eq!(None, reader_parity::<Option<u8>>(b"_\r\n"));
+    assert!(reader_parity::<bool>(b"|1\r\n+a\r\n:1\r\n#t\r\n"));
+    assert_eq!(
+        "Some string",
+        reader_parity::<String>(b"=15\r\ntxt:Some string\r\n")
+    );
+    assert_eq!(
+        b"\x00\r\n".to_vec(),
+        reader_parity::<serde_bytes::ByteBuf>(b"$3\r\n\x00\r\n\r\n").into_vec()
+    );
+}
+
+#[test]
+fn reader_streams_large_map() {
+    let mut input = b"%10000\r\n".to_vec();
+    for i in 0..10_000 {
+        let field = format!("f{}", i);
+        input.extend(format!("${}\r\n{}\r\n:{}\r\n", field.len(), field, i).bytes());
+    }
+
+    let map: HashMap<String, u32> = resp::from_reader(Chunked {
+        input: &input,
+        chunk: 7,
+    })
+    .unwrap();
+
+    assert_eq!(10_000, map.len());
+    assert_eq!(Some(&9_999), map.get("f9999"));
+}
+
+#[test]
+fn reader_consecutive_replies() {
+    let input = b"+OK\r\n:5\r\n$-1\r\n";
+    let mut de = Deserializer::from_reader(Chunked { input, chunk: 1 });
+
+    let ok: String = serde::Deserialize::deserialize(&mut de).unwrap();
+    let count: u32 = serde::Deserialize::deserialize(&mut de).unwrap();
+    let missing: Option<String> = serde::Deserialize::deserialize(&mut de).unwrap();
+
+    assert_eq!(("OK".to_owned(), 5, None), (ok, count, missing));
+    de.end().unwrap();
+}
+
+#[test]
+fn reader_malformed_input() {
+    assert!(matches!(
+        resp::from_reader::<_, String>(Cursor::new(b"$5\r\nab")),
+        Err(Error::Eof)
+    ));
+    assert!(matches!(
+        resp::from_reader::<_, String>(Cursor::new(b"$2\r\nabc\r\n")),
+        Err(Error::Syntax(6, _))
+    ));
+    assert!(matches!(
+        resp::from_reader::<_, String>(Cursor::new(b"+OK\r\n+OK\r\n")),
+        Err(Error::TrailingBytes(5))
+    ));
+    assert!(matches!(
+        resp::from_reader::<_, String>(Cursor::new(b"-ERR nope\r\n")),
+        Err(Error::Server(_))
+    ));
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Profile {
+    name: String,
+    admin: bool,
+    score: f64,
+    email: Option<String>,
+    tags: Vec<String>,
+}
+
+fn profile() -> Profile {
+    Profile {
+        name: "ada".to_owned(),
+        admin: true,
+        score: 1.5,
+        email: None,
+        tags: vec!["a".to_owned(), "b".to_owned()],
+    }
+}
+
+#[test]
+fn write_resp2_reply() {
+    let bytes = resp::to_vec(&profile()).unwrap();
+
+    let expected = serde_redis::encode::to_redis_value(&profile()).unwrap();
+    assert_eq!(expected, redis::parse_redis_value(&bytes).unwrap());
+    assert_eq!(profile(), resp::from_slice::<Profile>(&bytes).unwrap());
+
+    assert_eq!(b"$2\r\n42\r\n".to_vec(), resp::to_vec(&42u8).unwrap());
+    assert_eq!(b"$-1\r\n".to_vec(), resp::to_vec(&None::<u8>).unwrap());
+}
+
+#[test]
+fn write_resp3_reply() {
+    let mut ser = resp::Serializer::resp3(Vec::new());
+    serde::Serialize::serialize(&profile(), &mut ser).unwrap();
+    let bytes = ser.into_inner();
+
+    let expected: &[u8] = b"%5\r\n$4\r\nname\r\n$3\r\nada\r\n$5\r\nadmin\r\n#t\r\n\
+                            $5\r\nscore\r\n,1.5\r\n$5\r\nemail\r\n_\r\n\
+                            $4\r\ntags\r\n*2\r\n$1\r\na\r\n$1\r\nb\r\n";
+    assert_eq!(expected, &bytes[..]);
+    assert_eq!(profile(), resp::from_slice::<Profile>(&bytes).unwrap());
+
+    let mut ser = resp::Serializer::resp3(Vec::new());
+    serde::Serialize::serialize(&(-3i64, u64::MAX), &mut ser).unwrap();
+    assert_eq!(
+        b"*2\r\n:-3\r\n(18446744073709551615\r\n".to_vec(),
+        ser.into_inner()
+    );
+}
+
+#[test]
+fn write_unknown_length_map() {
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Flat {
+        id: u32,
+        #[serde(flatten)]
+        extra: HashMap<String, String>,
+    }
+
+    let mut extra = HashMap::new();
+    extra.insert("k".to_owned(), "v".to_owned());
+    let flat = Flat { id: 7, extra };
+
+    let bytes = resp::to_vec(&flat).unwrap();
+    assert_eq!(
+        b"*4\r\n$2\r\nid\r\n$1\r\n7\r\n$1\r\nk\r\n$1\r\nv\r\n".to_vec(),
+        bytes
+    );
+
+    let mut ser = resp::Serializer::resp3(Vec::new());
+    serde::Serialize::serialize(&flat, &mut ser).unwrap();
+    assert_eq!(flat, resp::from_slice::<Flat>(&ser.into_inner()).unwrap());
+}
+
+#[test]
+fn write_command() {
+    #[derive(Serialize)]
+    struct Login<'a> {
+        name: &'a str,
+        admin: bool,
+        score: f64,
+        email: Option<&'a str>,
+    }
+
+    let login = Login {
+        name: "ada",
+        admin: true,
+        score: 1.5,
+        email: None,
+    };
+    let bytes = resp::command_to_vec(&("HSET", "user:1", login)).unwrap();
+
+    let expected: &[u8] = b"*8\r\n$4\r\nHSET\r\n$6\r\nuser:1\r\n$4\r\nname\r\n$3\r\nada\r\n\
+                            $5\r\nadmin\r\n$1\r\n1\r\n$5\r\nscore\r\n$3\r\n1.5\r\n";
+    assert_eq!(expected, &bytes[..]);
+
+    let cmd = redis::cmd("SET")
+        .arg("k")
+        .arg(5)
+        .arg("EX")
+        .arg(10)
+        .get_packed_command();
+    assert_eq!(
+        cmd,
+        resp::command_to_vec(&("SET", "k", 5, None::<u8>, ["EX", "10"])).unwrap()
+    );
+}
+
+#[test]
+fn write_command_rejects_nested_fields() {
+    // `tags` would otherwise spill into the pairs that follow it
+    assert!(matches!(
+        resp::command_to_vec(&("HSET", "user:1", profile())),
+        Err(Error::Encode(serde_redis::encode::Error::ExpectedHash(_)))
+    ));
+    assert!(matches!(
+        resp::command_to_vec(&("SET", "k", u128::MAX)),
+        Err(Error::Encode(_))
+    ));
+    assert!(matches!(resp::to_vec(&u128::MAX), Err(Error::Encode(_))));
+}
+
+#[test]
+fn write_unsupported_type() {
+    #[derive(Serialize)]
+    enum Shape {
+        Circle(f64),
+    }
+
+    assert!(matches!(
+        resp::to_vec(&Shape::Circle(1.0)),
+        Err(Error::Encode(_))
+    ));
+}
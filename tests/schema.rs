@@ -0,0 +1,234 @@
+#[macro_use]
+extern crate serde_derive;
+
+use std::collections::HashMap;
+
+use serde_redis::schema::{self, Field, Shape, Variant};
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+enum Plan {
+    Free,
+    Pro,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Address {
+    city: String,
+    zip: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Account {
+    id: u64,
+    #[serde(rename = "n")]
+    name: String,
+    plan: Plan,
+    score: f64,
+    active: bool,
+    tags: Vec<String>,
+    address: Option<Address>,
+    limits: HashMap<String, u32>,
+}
+
+#[test]
+fn fields_reports_names_and_shapes() {
+    let fields = schema::fields::<Account>().unwrap();
+
+    let expected = vec![
+        Field {
+            name: "id",
+            shape: Shape::Int,
+        },
+        Field {
+            name: "n",
+            shape: Shape::Str,
+        },
+        Field {
+            name: "plan",
+            shape: Shape::Enum {
+                name: "Plan",
+                variants: vec![
+                    Variant {
+                        name: "Free",
+                        shape: Shape::Unit,
+                    },
+                    Variant {
+                        name: "Pro",
+                        shape: Shape::Unit,
+                    },
+                ],
+            },
+        },
+        Field {
+            name: "score",
+            shape: Shape::Float,
+        },
+        Field {
+            name: "active",
+            shape: Shape::Bool,
+        },
+        Field {
+            name: "tags",
+            shape: Shape::Seq(Box::new(Shape::Str)),
+        },
+        Field {
+            name: "address",
+            shape: Shape::Option(Box::new(Shape::Struct {
+                name: "Address",
+                fields: vec![
+                    Field {
+                        name: "city",
+                        shape: Shape::Str,
+                    },
+                    Field {
+                        name: "zip",
+                        shape: Shape::Option(Box::new(Shape::Int)),
+                    },
+                ],
+            })),
+        },
+        Field {
+            name: "limits",
+            shape: Shape::Map(Box::new(Shape::Str), Box::new(Shape::Int)),
+        },
+    ];
+    assert_eq!(expected, fields);
+}
+
+#[test]
+fn optional_fields_can_be_listed() {
+    let optional = schema::fields::<Account>()
+        .unwrap()
+        .into_iter()
+        .filter(|field| field.shape.is_optional())
+        .map(|field| field.name)
+        .collect::<Vec<_>>();
+
+    assert_eq!(vec!["address"], optional);
+}
+
+#[test]
+fn shape_of_tuple_and_newtype() {
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Score(f64);
+
+    let shape = schema::shape::<(String, Score)>().unwrap();
+
+    assert_eq!(Shape::Tuple(vec![Shape::Str, Shape::Float]), shape);
+}
+
+#[test]
+fn enum_variants_report_their_content() {
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    enum Billing {
+        Trial,
+        Card(String),
+        Invoice { days: u8, plan: Plan },
+        Credit(u32, Option<Plan>),
+    }
+
+    let shape = schema::shape::<Vec<Billing>>().unwrap();
+
+    let plan = || Shape::Enum {
+        name: "Plan",
+        variants: vec![
+            Variant {
+                name: "Free",
+                shape: Shape::Unit,
+            },
+            Variant {
+                name: "Pro",
+                shape: Shape::Unit,
+            },
+        ],
+    };
+    let expected = Shape::Seq(Box::new(Shape::Enum {
+        name: "Billing",
+        variants: vec![
+            Variant {
+                name: "Trial",
+                shape: Shape::Unit,
+            },
+            Variant {
+                name: "Card",
+                shape: Shape::Str,
+            },
+            Variant {
+                name: "Invoice",
+                shape: Shape::Struct {
+                    name: "Invoice",
+                    fields: vec![
+                        Field {
+                            name: "days",
+                            shape: Shape::Int,
+                        },
+                        Field {
+                            name: "plan",
+                            shape: plan(),
+                        },
+                    ],
+                },
+            },
+            Variant {
+                name: "Credit",
+                shape: Shape::Tuple(vec![Shape::Int, Shape::Option(Box::new(plan()))]),
+            },
+        ],
+    }));
+    assert_eq!(expected, shape);
+}
+
+#[test]
+fn recursive_enums_terminate() {
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    enum Expr {
+        Lit(i64),
+        Add(Box<Expr>, Box<Expr>),
+    }
+
+    let shape = schema::shape::<Expr>().unwrap();
+
+    let variants = match shape {
+        Shape::Enum { variants, .. } => variants,
+        shape => panic!("expected an enum, got {:?}", shape),
+    };
+    assert_eq!(Shape::Int, variants[0].shape);
+    // The nested `Expr` reports its variants, with only the first traced
+    let inner = match variants[1].shape {
+        Shape::Tuple(ref shapes) => &shapes[0],
+        ref shape => panic!("expected a tuple, got {:?}", shape),
+    };
+    match *inner {
+        Shape::Enum { ref variants, .. } => {
+            assert_eq!(Shape::Int, variants[0].shape);
+            assert_eq!(Shape::Any, variants[1].shape);
+        }
+        ref shape => panic!("expected an enum, got {:?}", shape),
+    }
+}
+
+#[test]
+fn recursive_types_terminate() {
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Node {
+        value: u8,
+        next: Option<Box<Node>>,
+    }
+
+    let fields = schema::fields::<Node>().unwrap();
+
+    assert_eq!("next", fields[1].name);
+    assert!(fields[1].shape.is_optional());
+}
+
+#[test]
+fn fields_of_non_struct_is_error() {
+    assert!(schema::fields::<Vec<u8>>().is_err());
+}
@@ -0,0 +1,78 @@
+#[macro_use]
+extern crate serde_derive;
+
+use redis::Value;
+use serde_redis::from_redis_value;
+use serde_redis::script::from_lua_value;
+
+fn data(s: &str) -> Value {
+    Value::Data(s.as_bytes().to_vec())
+}
+
+#[test]
+fn bool_from_lua_true_and_false() {
+    assert!(from_lua_value::<bool, _>(Value::Int(1)).unwrap());
+    assert!(!from_lua_value::<bool, _>(Value::Nil).unwrap());
+    assert!(!from_lua_value::<bool, _>(Value::Int(0)).unwrap());
+    assert!(from_lua_value::<bool, _>(data("true")).unwrap());
+    assert!(from_lua_value::<bool, _>(Value::Int(2)).is_err());
+}
+
+#[test]
+fn bool_from_int_needs_lua_mode() {
+    assert!(from_redis_value::<bool, _>(Value::Int(1)).is_err());
+}
+
+#[test]
+fn truncated_table_pads_tuple() {
+    // {'owner', false, 'x'} arrives as ['owner']
+    let reply = Value::Bulk(vec![data("owner")]);
+
+    let (owner, acquired, extra): (String, bool, Option<String>) = from_lua_value(&reply).unwrap();
+
+    assert_eq!("owner", owner);
+    assert!(!acquired);
+    assert_eq!(None, extra);
+}
+
+#[test]
+fn truncated_table_is_error_outside_lua_mode() {
+    let reply = Value::Bulk(vec![data("owner")]);
+
+    assert!(from_redis_value::<(String, bool), _>(&reply).is_err());
+}
+
+#[test]
+fn nested_values_use_lua_mode() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Job {
+        id: u64,
+        done: bool,
+        flags: Vec<bool>,
+    }
+
+    let reply = Value::Bulk(vec![
+        data("id"),
+        Value::Int(4),
+        data("done"),
+        Value::Int(1),
+        data("flags"),
+        Value::Bulk(vec![Value::Int(1), Value::Int(0)]),
+    ]);
+
+    let job: Job = from_lua_value(&reply).unwrap();
+
+    let expected = Job {
+        id: 4,
+        done: true,
+        flags: vec![true, false],
+    };
+    assert_eq!(expected, job);
+}
+
+#[test]
+fn status_reply_as_string() {
+    let status: String = from_lua_value(Value::Status("QUEUED".to_owned())).unwrap();
+
+    assert_eq!("QUEUED", status);
+}
@@ -0,0 +1,439 @@
+#[macro_use]
+extern crate serde_derive;
+
+use redis::Value;
+use serde_redis::stream::{
+    AutoClaimReply, ClaimReply, PendingConsumer, PendingEntry, PendingSummary, StreamEntry,
+    StreamId, StreamIdArg, StreamReadReply, XAdd,
+};
+use serde_redis::{from_redis_value, resp};
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Click {
+    url: String,
+    count: u32,
+}
+
+fn data(s: &str) -> Value {
+    Value::Data(s.as_bytes().to_vec())
+}
+
+fn entry(id: &str, url: &str, count: &str) -> Value {
+    Value::Bulk(vec![
+        data(id),
+        Value::Bulk(vec![data("url"), data(url), data("count"), data(count)]),
+    ])
+}
+
+#[test]
+fn stream_id_parses_and_displays() {
+    let id: StreamId = "1700000000000-3".parse().unwrap();
+
+    assert_eq!(StreamId::new(1700000000000, 3), id);
+    assert_eq!("1700000000000-3", id.to_string());
+    assert_eq!(StreamId::new(5, 0), "5".parse().unwrap());
+    assert!("5-x".parse::<StreamId>().is_err());
+}
+
+#[test]
+fn xrange_decodes_entries() {
+    let reply = Value::Bulk(vec![entry("1-0", "/a", "1"), entry("1-1", "/b", "2")]);
+
+    let entries: Vec<StreamEntry<Click>> = from_redis_value(&reply).unwrap();
+
+    let expected = vec![
+        StreamEntry {
+            id: StreamId::new(1, 0),
+            fields: Click {
+                url: "/a".to_owned(),
+                count: 1,
+            },
+        },
+        StreamEntry {
+            id: StreamId::new(1, 1),
+            fields: Click {
+                url: "/b".to_owned(),
+                count: 2,
+            },
+        },
+    ];
+    assert_eq!(expected, entries);
+}
+
+#[test]
+fn xread_decodes_entries_per_key() {
+    let reply = Value::Bulk(vec![
+        Value::Bulk(vec![
+            data("clicks"),
+            Value::Bulk(vec![entry("1-0", "/a", "1")]),
+        ]),
+        Value::Bulk(vec![
+            data("other"),
+            Value::Bulk(vec![entry("2-0", "/b", "2"), entry("2-1", "/c", "3")]),
+        ]),
+    ]);
+
+    let reply: StreamReadReply<Click> = from_redis_value(reply).unwrap();
+
+    assert_eq!(2, reply.keys.len());
+    assert_eq!("clicks", reply.keys[0].key);
+    assert_eq!("other", reply.keys[1].key);
+    let ids = reply
+        .entries()
+        .map(|e| e.id.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["1-0", "2-0", "2-1"], ids);
+    assert!(!reply.is_empty());
+}
+
+#[test]
+fn xread_timeout_is_empty_reply() {
+    let reply: StreamReadReply<Click> = from_redis_value(Value::Nil).unwrap();
+
+    assert!(reply.is_empty());
+    assert!(reply.keys.is_empty());
+}
+
+#[test]
+fn deleted_entry_fields_decode_as_none() {
+    let reply = Value::Bulk(vec![
+        Value::Bulk(vec![data("1-0"), Value::Nil]),
+        entry("1-1", "/b", "2"),
+    ]);
+
+    let entries: Vec<StreamEntry<Option<Click>>> = from_redis_value(reply).unwrap();
+
+    assert_eq!(None, entries[0].fields);
+    assert!(entries[1].fields.is_some());
+}
+
+#[test]
+fn stream_id_orders_by_ms_then_seq() {
+    let mut ids = vec![
+        StreamId::new(2, 0),
+        StreamId::new(1, 10),
+        StreamId::new(1, 2),
+    ];
+    ids.sort();
+
+    assert_eq!(
+        vec![
+            StreamId::new(1, 2),
+            StreamId::new(1, 10),
+            StreamId::new(2, 0)
+        ],
+        ids
+    );
+}
+
+#[test]
+fn stream_id_next_and_prev() {
+    assert_eq!(Some(StreamId::new(1, 1)), StreamId::new(1, 0).next());
+    assert_eq!(Some(StreamId::new(2, 0)), StreamId::new(1, u64::MAX).next());
+    assert_eq!(None, StreamId::MAX.next());
+
+    assert_eq!(Some(StreamId::new(1, 0)), StreamId::new(1, 1).prev());
+    assert_eq!(Some(StreamId::new(0, u64::MAX)), StreamId::new(1, 0).prev());
+    assert_eq!(None, StreamId::MIN.prev());
+}
+
+#[test]
+fn stream_id_decodes_from_data_and_status() {
+    let data: StreamId = from_redis_value(data("7-1")).unwrap();
+    let status: StreamId = from_redis_value(Value::Status("7-2".to_owned())).unwrap();
+
+    assert_eq!(StreamId::new(7, 1), data);
+    assert_eq!(StreamId::new(7, 2), status);
+}
+
+#[test]
+fn stream_id_serializes_to_canonical_text() {
+    let value = serde_redis::to_redis_value(&StreamId::new(7, 1)).unwrap();
+
+    assert_eq!(data("7-1"), value);
+}
+
+#[test]
+fn stream_id_args() {
+    let cmd = redis::cmd("XRANGE")
+        .arg("s")
+        .arg(StreamIdArg::Min)
+        .arg(StreamId::new(5, 1))
+        .get_packed_command();
+
+    let expected = redis::cmd("XRANGE")
+        .arg("s")
+        .arg("-")
+        .arg("5-1")
+        .get_packed_command();
+    assert_eq!(expected, cmd);
+}
+
+#[test]
+fn stream_id_arg_parses_tokens() {
+    let args = ["*", "$", ">", "-", "+", "3-4"]
+        .iter()
+        .map(|s| s.parse::<StreamIdArg>().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        vec![
+            StreamIdArg::Auto,
+            StreamIdArg::Last,
+            StreamIdArg::Undelivered,
+            StreamIdArg::Min,
+            StreamIdArg::Max,
+            StreamIdArg::Id(StreamId::new(3, 4)),
+        ],
+        args
+    );
+    let text = args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+    assert_eq!(vec!["*", "$", ">", "-", "+", "3-4"], text);
+}
+
+#[derive(Serialize)]
+struct Event {
+    kind: String,
+    user: Option<u64>,
+}
+
+fn event() -> Event {
+    Event {
+        kind: "login".to_owned(),
+        user: None,
+    }
+}
+
+#[test]
+fn xadd_cmd_flattens_fields() {
+    let cmd = XAdd::new("events", &event()).cmd().unwrap();
+
+    let expected = redis::cmd("XADD")
+        .arg("events")
+        .arg("*")
+        .arg("kind")
+        .arg("login")
+        .get_packed_command();
+    assert_eq!(expected, cmd.get_packed_command());
+}
+
+#[test]
+fn xadd_cmd_with_options() {
+    let cmd = XAdd::new("events", &event())
+        .nomkstream()
+        .maxlen_approx(1000)
+        .id(StreamId::new(5, 1))
+        .cmd()
+        .unwrap();
+
+    let expected = redis::cmd("XADD")
+        .arg("events")
+        .arg("NOMKSTREAM")
+        .arg("MAXLEN")
+        .arg("~")
+        .arg(1000)
+        .arg("5-1")
+        .arg("kind")
+        .arg("login")
+        .get_packed_command();
+    assert_eq!(expected, cmd.get_packed_command());
+}
+
+#[test]
+fn xadd_cmd_minid() {
+    let cmd = XAdd::new("events", &event())
+        .minid(StreamId::new(9, 0))
+        .cmd()
+        .unwrap();
+
+    let expected = redis::cmd("XADD")
+        .arg("events")
+        .arg("MINID")
+        .arg("9-0")
+        .arg("*")
+        .arg("kind")
+        .arg("login")
+        .get_packed_command();
+    assert_eq!(expected, cmd.get_packed_command());
+}
+
+#[test]
+fn xadd_without_fields_is_error() {
+    #[derive(Serialize)]
+    struct Empty {
+        a: Option<u8>,
+    }
+
+    assert!(XAdd::new("events", &Empty { a: None }).cmd().is_err());
+}
+
+#[test]
+fn xpending_summary_decodes() {
+    let reply = Value::Bulk(vec![
+        Value::Int(3),
+        data("1-0"),
+        data("4-0"),
+        Value::Bulk(vec![
+            Value::Bulk(vec![data("worker-1"), data("2")]),
+            Value::Bulk(vec![data("worker-2"), data("1")]),
+        ]),
+    ]);
+
+    let summary: PendingSummary = from_redis_value(&reply).unwrap();
+
+    let expected = PendingSummary {
+        count: 3,
+        min: Some(StreamId::new(1, 0)),
+        max: Some(StreamId::new(4, 0)),
+        consumers: vec![
+            PendingConsumer {
+                name: "worker-1".to_owned(),
+                pending: 2,
+            },
+            PendingConsumer {
+                name: "worker-2".to_owned(),
+                pending: 1,
+            },
+        ],
+    };
+    assert_eq!(expected, summary);
+}
+
+#[test]
+fn xpending_summary_without_pending_entries() {
+    let reply = Value::Bulk(vec![Value::Int(0), Value::Nil, Value::Nil, Value::Nil]);
+
+    let summary: PendingSummary = from_redis_value(&reply).unwrap();
+
+    assert_eq!(PendingSummary::default(), summary);
+}
+
+#[test]
+fn xpending_extended_decodes() {
+    let reply = Value::Bulk(vec![Value::Bulk(vec![
+        data("1-0"),
+        data("worker-1"),
+        Value::Int(9000),
+        Value::Int(2),
+    ])]);
+
+    let pending: Vec<PendingEntry> = from_redis_value(&reply).unwrap();
+
+    let expected = vec![PendingEntry {
+        id: StreamId::new(1, 0),
+        consumer: "worker-1".to_owned(),
+        idle_ms: 9000,
+        deliveries: 2,
+    }];
+    assert_eq!(expected, pending);
+}
+
+#[test]
+fn xclaim_with_deleted_entry() {
+    let reply = Value::Bulk(vec![
+        entry("1-0", "/a", "1"),
+        Value::Bulk(vec![data("1-1"), Value::Nil]),
+    ]);
+
+    let claimed: Vec<StreamEntry<Option<Click>>> = from_redis_value(&reply).unwrap();
+
+    assert!(claimed[0].fields.is_some());
+    assert_eq!(StreamId::new(1, 1), claimed[1].id);
+    assert_eq!(None, claimed[1].fields);
+}
+
+#[test]
+fn xclaim_reply_decodes_entries() {
+    let reply = Value::Bulk(vec![
+        entry("1-0", "/a", "1"),
+        Value::Bulk(vec![data("1-1"), Value::Nil]),
+        entry("1-2", "/b", "2"),
+    ]);
+
+    let reply: ClaimReply<Click> = from_redis_value(&reply).unwrap();
+
+    match reply {
+        ClaimReply::Entries { entries, deleted } => {
+            let ids = entries.iter().map(|entry| entry.id).collect::<Vec<_>>();
+            assert_eq!(vec![StreamId::new(1, 0), StreamId::new(1, 2)], ids);
+            assert_eq!("/b", entries[1].fields.url);
+            assert_eq!(vec![StreamId::new(1, 1)], deleted);
+        }
+        reply => panic!("expected entries, got {:?}", reply),
+    }
+}
+
+#[test]
+fn xclaim_reply_decodes_justid() {
+    let reply = Value::Bulk(vec![data("1-0"), data("1-2")]);
+
+    let reply: ClaimReply<Click> = from_redis_value(&reply).unwrap();
+
+    assert_eq!(
+        ClaimReply::Ids(vec![StreamId::new(1, 0), StreamId::new(1, 2)]),
+        reply
+    );
+
+    let reply: ClaimReply<Click> = resp::from_slice(b"*2\r\n$3\r\n1-0\r\n$3\r\n1-2\r\n").unwrap();
+    assert_eq!(
+        ClaimReply::Ids(vec![StreamId::new(1, 0), StreamId::new(1, 2)]),
+        reply
+    );
+}
+
+#[test]
+fn xclaim_reply_from_resp() {
+    let input = b"*2\r\n*2\r\n$3\r\n1-0\r\n*4\r\n$3\r\nurl\r\n$2\r\n/a\r\n$5\r\ncount\r\n$1\r\n1\r\n*2\r\n$3\r\n1-1\r\n*-1\r\n";
+
+    let reply: ClaimReply<Click> = resp::from_slice(input).unwrap();
+
+    let expected = ClaimReply::Entries {
+        entries: vec![StreamEntry {
+            id: StreamId::new(1, 0),
+            fields: Click {
+                url: "/a".to_owned(),
+                count: 1,
+            },
+        }],
+        deleted: vec![StreamId::new(1, 1)],
+    };
+    assert_eq!(expected, reply);
+}
+
+#[test]
+fn xclaim_reply_rejects_mixed_forms() {
+    let reply = Value::Bulk(vec![entry("1-0", "/a", "1"), data("1-1")]);
+
+    assert!(from_redis_value::<ClaimReply<Click>, _>(&reply).is_err());
+    assert_eq!(
+        ClaimReply::default(),
+        from_redis_value::<ClaimReply<Click>, _>(&Value::Bulk(vec![])).unwrap()
+    );
+}
+
+#[test]
+fn xautoclaim_decodes() {
+    let reply = Value::Bulk(vec![
+        data("2-0"),
+        Value::Bulk(vec![entry("1-0", "/a", "1")]),
+        Value::Bulk(vec![data("1-1")]),
+    ]);
+
+    let reply: AutoClaimReply<Click> = from_redis_value(&reply).unwrap();
+
+    assert_eq!(StreamId::new(2, 0), reply.next);
+    assert!(!reply.is_done());
+    assert_eq!(1, reply.entries.len());
+    assert_eq!(vec![StreamId::new(1, 1)], reply.deleted);
+}
+
+#[test]
+fn xautoclaim_redis_6_reply() {
+    let reply = Value::Bulk(vec![data("0-0"), Value::Bulk(vec![])]);
+
+    let reply: AutoClaimReply<Click> = from_redis_value(&reply).unwrap();
+
+    assert!(reply.is_done());
+    assert!(reply.entries.is_empty());
+    assert!(reply.deleted.is_empty());
+}
@@ -0,0 +1,313 @@
+#![cfg(feature = "testing")]
+
+#[macro_use]
+extern crate serde_derive;
+
+use redis::{ErrorKind, RedisResult, Value};
+use serde_redis::testing::{self, Layout, MockConnection};
+use serde_redis::transaction::{self, Exec};
+use serde_redis::{from_redis_value, RedisSerdeExt};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct User {
+    name: String,
+    visits: u32,
+    email: Option<String>,
+}
+
+fn ada() -> User {
+    User {
+        name: "ada".to_owned(),
+        visits: 3,
+        email: Some("ada@example.com".to_owned()),
+    }
+}
+
+fn data(s: &str) -> Value {
+    Value::Data(s.as_bytes().to_vec())
+}
+
+#[test]
+fn struct_roundtrips_through_hash() {
+    let mut con = MockConnection::new();
+
+    con.hset_struct("user:1", &ada()).unwrap();
+    let read: User = con.hget_struct("user:1").unwrap();
+
+    assert_eq!(ada(), read);
+}
+
+#[test]
+fn reply_shapes() {
+    let mut con = MockConnection::new();
+
+    let set: Value = redis::cmd("SET").arg("k").arg("v").query(&mut con).unwrap();
+    let missing: Value = redis::cmd("GET").arg("nope").query(&mut con).unwrap();
+    let kind: Value = redis::cmd("TYPE").arg("k").query(&mut con).unwrap();
+    let ping: Value = redis::cmd("PING").query(&mut con).unwrap();
+    let pushed: Value = redis::cmd("RPUSH")
+        .arg("l")
+        .arg(&["a", "b"])
+        .query(&mut con)
+        .unwrap();
+    let nx: Value = redis::cmd("SET")
+        .arg("k")
+        .arg("w")
+        .arg("NX")
+        .query(&mut con)
+        .unwrap();
+
+    assert_eq!(Value::Okay, set);
+    assert_eq!(Value::Nil, missing);
+    assert_eq!(Value::Status("string".to_owned()), kind);
+    assert_eq!(Value::Status("PONG".to_owned()), ping);
+    assert_eq!(Value::Int(2), pushed);
+    assert_eq!(Value::Nil, nx);
+}
+
+#[test]
+fn collections() {
+    let mut con = MockConnection::new();
+
+    redis::cmd("LPUSH")
+        .arg("l")
+        .arg(&[1, 2, 3])
+        .execute(&mut con);
+    let list: Vec<u32> = con.lrange_as("l", 0, -2).unwrap();
+    assert_eq!(vec![3, 2], list);
+
+    redis::cmd("SADD")
+        .arg("s")
+        .arg(&["b", "a", "b"])
+        .execute(&mut con);
+    let set: Vec<String> = con.smembers_as("s").unwrap();
+    assert_eq!(vec!["a", "b"], set);
+
+    redis::cmd("ZADD")
+        .arg("z")
+        .arg(&["2", "b", "1.5", "a", "2", "c"])
+        .execute(&mut con);
+    let ranked: Vec<(String, f64)> = redis::cmd("ZREVRANGE")
+        .arg("z")
+        .arg(0)
+        .arg(-1)
+        .arg("WITHSCORES")
+        .query(&mut con)
+        .unwrap();
+    assert_eq!(
+        vec![
+            ("c".to_owned(), 2.0),
+            ("b".to_owned(), 2.0),
+            ("a".to_owned(), 1.5)
+        ],
+        ranked
+    );
+
+    // Removing the last element deletes the key
+    redis::cmd("SREM")
+        .arg("s")
+        .arg(&["a", "b"])
+        .execute(&mut con);
+    let keys: Vec<String> = redis::cmd("KEYS").arg("*").query(&mut con).unwrap();
+    assert_eq!(vec!["l", "z"], keys);
+}
+
+#[test]
+fn pipeline() {
+    let mut con = MockConnection::new();
+
+    let (set, count, value): (bool, i64, Option<String>) = redis::pipe()
+        .set("a", "1")
+        .incr("a", 2)
+        .get("b")
+        .query(&mut con)
+        .unwrap();
+
+    assert!(set);
+    assert_eq!(3, count);
+    assert_eq!(None, value);
+    assert_eq!(3, con.commands().len());
+}
+
+#[test]
+fn transaction_commits() {
+    let mut con = MockConnection::new();
+    let mut pipe = redis::pipe();
+    pipe.atomic().incr("spent", 5).get("owner");
+
+    let reply: Exec<(i64, Option<String>)> = transaction::exec(&pipe, &mut con).unwrap();
+
+    assert_eq!(Exec::Committed((5, None)), reply);
+}
+
+#[test]
+fn watch_aborts_exec_after_change() {
+    let mut con = MockConnection::new();
+
+    redis::cmd("WATCH").arg("k").execute(&mut con);
+    redis::cmd("SET").arg("k").arg("changed").execute(&mut con);
+    let reply: Value = redis::pipe()
+        .atomic()
+        .set("k", "mine")
+        .query(&mut con)
+        .unwrap();
+
+    assert_eq!(Value::Nil, reply);
+    let value: String = con.get_as("k").unwrap();
+    assert_eq!("changed", value);
+}
+
+#[test]
+fn transaction_update() {
+    let mut con = MockConnection::new();
+    con.hset_struct("user:1", &ada()).unwrap();
+
+    let reply = transaction::update(&mut con, "user:1", 3, |user: &User| User {
+        visits: user.visits + 1,
+        email: None,
+        ..user.clone()
+    })
+    .unwrap();
+
+    let expected = User {
+        visits: 4,
+        email: None,
+        ..ada()
+    };
+    assert_eq!(Exec::Committed(expected.clone()), reply);
+    assert_eq!(expected, con.hget_struct("user:1").unwrap());
+}
+
+#[test]
+fn errors() {
+    let mut con = MockConnection::new();
+    redis::cmd("SET").arg("k").arg("v").execute(&mut con);
+
+    let wrong_type: RedisResult<Value> = redis::cmd("HGETALL").arg("k").query(&mut con);
+    let not_integer: RedisResult<Value> = redis::cmd("INCR").arg("k").query(&mut con);
+    let unknown: RedisResult<Value> = redis::cmd("NOPE").query(&mut con);
+
+    assert_eq!(Some("WRONGTYPE"), wrong_type.unwrap_err().code());
+    assert_eq!(ErrorKind::ResponseError, not_integer.unwrap_err().kind());
+    assert!(unknown.unwrap_err().to_string().contains("unknown command"));
+
+    let overflow: RedisResult<Value> = redis::cmd("DECRBY").arg("n").arg(i64::MIN).query(&mut con);
+    assert!(overflow
+        .unwrap_err()
+        .to_string()
+        .contains("decrement would overflow"));
+}
+
+#[test]
+fn float_increments_refuse_nan_and_infinity() {
+    let mut con = MockConnection::new();
+    redis::cmd("ZADD")
+        .arg("z")
+        .arg(&["inf", "a"])
+        .execute(&mut con);
+    redis::cmd("SET").arg("f").arg("1.5").execute(&mut con);
+
+    let nan_score: RedisResult<Value> = redis::cmd("ZINCRBY")
+        .arg("z")
+        .arg("-inf")
+        .arg("a")
+        .query(&mut con);
+    let infinite: RedisResult<Value> = redis::cmd("INCRBYFLOAT")
+        .arg("f")
+        .arg("inf")
+        .query(&mut con);
+
+    assert!(nan_score
+        .unwrap_err()
+        .to_string()
+        .contains("resulting score is not a number (NaN)"));
+    assert!(infinite
+        .unwrap_err()
+        .to_string()
+        .contains("increment would produce NaN or Infinity"));
+
+    // Neither value was changed
+    let ranked: Vec<(String, f64)> = redis::cmd("ZRANGE")
+        .arg("z")
+        .arg(0)
+        .arg(-1)
+        .arg("WITHSCORES")
+        .query(&mut con)
+        .unwrap();
+    let f: f64 = redis::cmd("GET").arg("f").query(&mut con).unwrap();
+    assert_eq!(vec![("a".to_owned(), f64::INFINITY)], ranked);
+    assert_eq!(1.5, f);
+}
+
+#[test]
+fn hash_reply_decodes_like_server() {
+    let mut con = MockConnection::new();
+    redis::cmd("HSET")
+        .arg("h")
+        .arg(&["name", "ada", "visits", "3"])
+        .execute(&mut con);
+
+    let value: Value = redis::cmd("HGETALL").arg("h").query(&mut con).unwrap();
+
+    assert_eq!(
+        Value::Bulk(vec![data("name"), data("ada"), data("visits"), data("3")]),
+        value
+    );
+    let user: User = from_redis_value(value).unwrap();
+    assert_eq!(None, user.email);
+}
+
+#[test]
+fn roundtrip_edge_cases() {
+    testing::assert_roundtrip(&ada());
+    testing::assert_roundtrip(&User {
+        name: String::new(),
+        visits: 0,
+        email: Some("émile@例え.jp".to_owned()),
+    });
+    testing::assert_roundtrip(&(u64::MAX, i64::MIN, f64::NAN, f32::INFINITY));
+    testing::assert_roundtrip(&vec![Some(String::new()), None]);
+}
+
+#[test]
+fn unit_roundtrips() {
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Marker;
+
+    testing::check_roundtrip(&()).unwrap();
+    testing::check_roundtrip(&Marker).unwrap();
+    testing::check_roundtrip(&vec![(), ()]).unwrap();
+}
+
+#[test]
+fn roundtrip_reports_layout() {
+    let err = testing::check_roundtrip(&Some(None::<u8>)).unwrap_err();
+    assert!(matches!(
+        err,
+        testing::Error::Mismatch {
+            layout: Layout::Value,
+            ..
+        }
+    ));
+
+    let err = testing::check_roundtrip(&u128::MAX).unwrap_err();
+    assert!(matches!(err, testing::Error::Encode(Layout::Value, _)));
+}
+
+#[test]
+#[should_panic(expected = "round trip through redis::Value")]
+fn assert_roundtrip_panics() {
+    testing::assert_roundtrip(&Some(None::<u8>));
+}
+
+#[cfg(feature = "proptest")]
+#[test]
+fn proptest_roundtrip() {
+    testing::proptest_roundtrip::<(u64, i32, f64, String, Option<bool>, Vec<char>)>();
+}
+
+#[cfg(feature = "quickcheck")]
+#[test]
+fn quickcheck_roundtrip() {
+    testing::quickcheck_roundtrip::<(u64, i64, f64, String, Option<u8>, Vec<String>)>();
+}
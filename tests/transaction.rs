@@ -0,0 +1,173 @@
+#[macro_use]
+extern crate serde_derive;
+
+mod common;
+
+use common::Replay;
+use redis::Value;
+use serde_redis::from_redis_value;
+use serde_redis::transaction::{self, Exec};
+
+fn data(s: &str) -> Value {
+    Value::Data(s.as_bytes().to_vec())
+}
+
+fn queued() -> Value {
+    Value::Status("QUEUED".to_owned())
+}
+
+fn transfer() -> redis::Pipeline {
+    let mut pipe = redis::pipe();
+    pipe.atomic().incr("spent", 5).get("owner");
+    pipe
+}
+
+#[test]
+fn exec_committed() {
+    let mut con = Replay::new(vec![
+        Value::Okay,
+        queued(),
+        queued(),
+        Value::Bulk(vec![Value::Int(5), data("ada")]),
+    ]);
+
+    let reply: Exec<(i64, String)> = transaction::exec(&transfer(), &mut con).unwrap();
+
+    assert_eq!(Exec::Committed((5, "ada".to_owned())), reply);
+}
+
+#[test]
+fn exec_aborted() {
+    let mut con = Replay::new(vec![Value::Okay, queued(), queued(), Value::Nil]);
+
+    let reply: Exec<(i64, String)> = transaction::exec(&transfer(), &mut con).unwrap();
+
+    assert!(reply.is_aborted());
+}
+
+#[test]
+fn from_replies_into_struct_by_position() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Transfer {
+        spent: i64,
+        owner: Option<String>,
+    }
+
+    let reply = Value::Bulk(vec![Value::Int(5), Value::Nil]);
+
+    let reply: Exec<Transfer> = transaction::from_replies(&transfer(), &reply).unwrap();
+
+    let expected = Transfer {
+        spent: 5,
+        owner: None,
+    };
+    assert_eq!(Some(expected), reply.committed());
+}
+
+#[test]
+fn from_replies_reports_failing_command() {
+    let reply = Value::Bulk(vec![data("x"), Value::Nil]);
+
+    let err =
+        transaction::from_replies::<(i64, Option<String>), _>(&transfer(), &reply).unwrap_err();
+
+    assert_eq!(0, err.index);
+    assert_eq!(Some("INCRBY".to_owned()), err.command);
+}
+
+#[test]
+fn deserialize_exec() {
+    let aborted: Exec<Vec<i64>> = from_redis_value(Value::Nil).unwrap();
+    let committed: Exec<Vec<i64>> = from_redis_value(Value::Bulk(vec![Value::Int(1)])).unwrap();
+
+    assert_eq!(Exec::Aborted, aborted);
+    assert_eq!(Exec::Committed(vec![1]), committed);
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Counter {
+    hits: u64,
+}
+
+fn read(hits: &str) -> Vec<Value> {
+    vec![Value::Okay, Value::Bulk(vec![data("hits"), data(hits)])]
+}
+
+#[test]
+fn update_retries_after_abort() {
+    let mut replies = read("1");
+    replies.extend(vec![Value::Okay, queued(), Value::Nil]);
+    replies.extend(read("2"));
+    replies.extend(vec![
+        Value::Okay,
+        queued(),
+        Value::Bulk(vec![Value::Int(0)]),
+    ]);
+    let mut con = Replay::new(replies);
+
+    let reply = transaction::update(&mut con, "counter", 3, |c: &Counter| Counter {
+        hits: c.hits + 1,
+    })
+    .unwrap();
+
+    assert_eq!(Exec::Committed(Counter { hits: 3 }), reply);
+    let expected = vec!["WATCH", "HGETALL", "MULTI", "WATCH", "HGETALL", "MULTI"];
+    assert_eq!(expected, con.sent_commands());
+}
+
+#[test]
+fn update_gives_up() {
+    let mut replies = read("1");
+    replies.extend(vec![Value::Okay, queued(), Value::Nil]);
+    let mut con = Replay::new(replies);
+
+    let reply = transaction::update(&mut con, "counter", 1, |c: &Counter| Counter {
+        hits: c.hits + 1,
+    })
+    .unwrap();
+
+    assert!(reply.is_aborted());
+}
+
+#[test]
+fn update_without_changes_unwatches() {
+    let mut replies = read("1");
+    replies.push(Value::Okay);
+    let mut con = Replay::new(replies);
+
+    let reply = transaction::update(&mut con, "counter", 1, |c: &Counter| Counter {
+        hits: c.hits,
+    })
+    .unwrap();
+
+    assert_eq!(Exec::Committed(Counter { hits: 1 }), reply);
+    assert_eq!(vec!["WATCH", "HGETALL", "UNWATCH"], con.sent_commands());
+}
+
+#[test]
+fn update_unwatches_when_decoding_fails() {
+    let replies = vec![Value::Okay, Value::Bulk(vec![]), Value::Okay];
+    let mut con = Replay::new(replies);
+
+    let err = transaction::update(&mut con, "counter", 3, |c: &Counter| Counter {
+        hits: c.hits + 1,
+    })
+    .unwrap_err();
+
+    assert_eq!(redis::ErrorKind::TypeError, err.kind());
+    assert_eq!(vec!["WATCH", "HGETALL", "UNWATCH"], con.sent_commands());
+}
+
+#[test]
+fn update_takes_owned_keys() {
+    let mut replies = read("1");
+    replies.push(Value::Okay);
+    let mut con = Replay::new(replies);
+    let key = format!("counter:{}", 1);
+
+    let reply =
+        transaction::update(&mut con, key, 1, |c: &Counter| Counter { hits: c.hits }).unwrap();
+
+    assert_eq!(Exec::Committed(Counter { hits: 1 }), reply);
+    assert!(String::from_utf8_lossy(&con.sent[0]).contains("counter:1"));
+}